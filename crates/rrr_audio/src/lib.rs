@@ -1,4 +1,6 @@
 mod output;
 mod player;
+mod probe;
 
 pub use player::*;
+pub use probe::duration;
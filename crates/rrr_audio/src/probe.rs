@@ -0,0 +1,47 @@
+use anyhow::Context;
+use std::{
+    io::{self, Cursor},
+    time::Duration,
+};
+use symphonia::{
+    core::{
+        errors::Error as SymphoniaError,
+        formats::{FormatOptions, FormatReader},
+        io::{MediaSourceStream, MediaSourceStreamOptions},
+    },
+    default::formats::MpaReader,
+};
+
+/// Find how long an mp3 plays for by walking its frames, without decoding them.
+///
+/// # Errors
+///
+/// Will return an error if `mp3` does not contain any mp3 frames.
+pub fn duration(mp3: &[u8]) -> anyhow::Result<Duration> {
+    let mss = MediaSourceStream::new(
+        Box::new(Cursor::new(mp3.to_owned())),
+        MediaSourceStreamOptions::default(),
+    );
+    let mut reader = MpaReader::try_new(mss, &FormatOptions::default())?;
+    let sample_rate = reader
+        .default_track()
+        .context("no tracks present")?
+        .codec_params
+        .sample_rate
+        .context("unknown sample rate")?;
+
+    let mut frames: u64 = 0;
+    loop {
+        match reader.next_packet() {
+            Ok(packet) => frames = frames.saturating_add(packet.dur()),
+            Err(SymphoniaError::IoError(err)) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                break;
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    Ok(Duration::from_secs_f64(
+        frames as f64 / f64::from(sample_rate),
+    ))
+}
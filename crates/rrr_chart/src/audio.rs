@@ -0,0 +1,68 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How the audio bundled with a chart is encoded.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Default, Eq, PartialEq, Hash, Debug)]
+pub enum AudioFormat {
+    #[default]
+    Mp3,
+    Adpcm,
+    /// Raw samples, little-endian.
+    Pcm,
+    /// Raw samples in the platform's native byte order.
+    PcmNativeEndian,
+    Nellymoser,
+    Aac,
+    Speex,
+}
+
+/// Describes the audio stream of a chart, as far as the source format tells us.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Default, Eq, PartialEq, Hash, Debug)]
+pub struct AudioInfo {
+    pub format: AudioFormat,
+    /// Samples per second.
+    pub sample_rate: u32,
+    pub is_stereo: bool,
+    pub is_16_bit: bool,
+    /// Samples the encoder added to the start of the stream that the player should skip.
+    pub latency_seek: i16,
+}
+
+impl AudioInfo {
+    /// How long the encoder delay described by `latency_seek` lasts, in milliseconds.
+    ///
+    /// Decoders that don't skip those samples play the song this much late,
+    /// so it can be used directly as a sync offset.
+    #[must_use]
+    pub fn latency_ms(&self) -> u32 {
+        if self.sample_rate == 0 || self.latency_seek <= 0 {
+            return 0;
+        }
+
+        (u64::from(self.latency_seek.unsigned_abs()) * 1000 / u64::from(self.sample_rate)) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_latency_ms() {
+        let audio = AudioInfo {
+            sample_rate: 44100,
+            latency_seek: 1105,
+            ..AudioInfo::default()
+        };
+        assert_eq!(audio.latency_ms(), 25);
+
+        let negative = AudioInfo {
+            latency_seek: -1105,
+            ..audio
+        };
+        assert_eq!(negative.latency_ms(), 0);
+        assert_eq!(AudioInfo::default().latency_ms(), 0);
+    }
+}
@@ -2,11 +2,16 @@
 use serde::{Deserialize, Serialize};
 
 use super::note::{NoteRow, RuntimeNote};
+use rrr_types::LaneCount;
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub struct RuntimeChart {
     pub notes: Vec<RuntimeNote>,
+    pub timing: Vec<TimingPoint>,
+    /// Every note's lane is below this.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub lane_count: LaneCount,
 }
 
 impl RuntimeChart {
@@ -14,19 +19,71 @@ impl RuntimeChart {
     pub fn new(notes: &[RuntimeNote]) -> Self {
         Self {
             notes: notes.to_vec(),
+            timing: Vec::new(),
+            lane_count: LaneCount::FOUR,
         }
     }
 
+    #[must_use]
+    pub fn with_timing(self, timing: &[TimingPoint]) -> Self {
+        Self {
+            timing: timing.to_vec(),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn with_lane_count(self, lane_count: LaneCount) -> Self {
+        Self { lane_count, ..self }
+    }
+
+    /// The timestamp of the last note, whichever order the notes are stored in.
+    ///
     /// # Errors
     ///
     /// Will return `anyhow::Error` if there is not at least 1 note in the chart.
     pub fn get_duration(&self) -> Result<u32, anyhow::Error> {
-        if let Some(last_note) = self.notes.first() {
-            Ok(last_note.timestamp)
+        self.notes
+            .iter()
+            .map(|note| note.timestamp)
+            .max()
+            .ok_or_else(|| anyhow::anyhow!("No notes in chart"))
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+/// A tempo that is in effect from `timestamp` until the next timing point.
+///
+/// Charts that only know note timestamps (like FFR swf charts) have no timing points.
+pub struct TimingPoint {
+    /// Milliseconds, can be negative when the first beat happens before the audio starts.
+    pub timestamp: i32,
+    pub micros_per_beat: u32,
+}
+
+impl TimingPoint {
+    #[must_use]
+    pub fn new(timestamp: i32, micros_per_beat: u32) -> Self {
+        Self {
+            timestamp,
+            micros_per_beat,
+        }
+    }
+
+    #[must_use]
+    pub fn bpm(&self) -> f64 {
+        if self.micros_per_beat == 0 {
+            0.
         } else {
-            Err(anyhow::anyhow!("No notes in chart"))
+            60_000_000. / f64::from(self.micros_per_beat)
         }
     }
+
+    #[must_use]
+    pub fn ms_per_beat(&self) -> f64 {
+        f64::from(self.micros_per_beat) / 1000.
+    }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -46,3 +103,33 @@ impl Beat {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NoteColor, NoteKind};
+    use rrr_types::Lane;
+
+    fn note(timestamp: u32) -> RuntimeNote {
+        RuntimeNote {
+            beat_position: 0,
+            color: NoteColor::Red,
+            lane: Lane(0),
+            timestamp,
+            kind: NoteKind::Tap,
+        }
+    }
+
+    #[test]
+    fn test_duration() -> Result<(), anyhow::Error> {
+        // Imported charts are sorted ascending, swf charts are stored newest first.
+        let ascending = RuntimeChart::new(&[note(100), note(250), note(900)]);
+        assert_eq!(ascending.get_duration()?, 900);
+
+        let descending = RuntimeChart::new(&[note(900), note(250), note(100)]);
+        assert_eq!(descending.get_duration()?, 900);
+
+        assert!(RuntimeChart::default().get_duration().is_err());
+        Ok(())
+    }
+}
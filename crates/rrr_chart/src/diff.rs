@@ -0,0 +1,259 @@
+//! Compares two versions of a chart note by note.
+
+use crate::{NoteKind, RuntimeChart, RuntimeNote};
+use rrr_types::Lane;
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DiffOptions {
+    /// Notes on the same lane this many milliseconds apart or closer are the same note.
+    pub tolerance: u32,
+    /// Notes on the same lane further apart than `tolerance` but within this many milliseconds
+    /// are reported as moved rather than removed and added.
+    pub max_shift: u32,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            tolerance: 0,
+            max_shift: 250,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+    /// Only in the second chart.
+    Added { note: RuntimeNote },
+
+    /// Only in the first chart.
+    Removed { note: RuntimeNote },
+
+    /// On the same lane in both, but at a different time.
+    Moved { from: RuntimeNote, to: RuntimeNote },
+
+    /// At the same time and lane in both, but a different color.
+    Recolored { from: RuntimeNote, to: RuntimeNote },
+}
+
+impl Change {
+    /// The note as it is in the first chart, or the added note.
+    #[must_use]
+    pub fn note(&self) -> &RuntimeNote {
+        match self {
+            Change::Added { note } | Change::Removed { note } => note,
+            Change::Moved { from, .. } | Change::Recolored { from, .. } => from,
+        }
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let note = self.note();
+        write!(f, "{}ms {}: ", note.timestamp, note.lane)?;
+
+        match self {
+            Change::Added { note } => write!(f, "added {:?} note.", note.color),
+            Change::Removed { note } => write!(f, "removed {:?} note.", note.color),
+            Change::Moved { from, to } => {
+                let shift = i64::from(to.timestamp) - i64::from(from.timestamp);
+                write!(f, "moved {shift:+}ms to {}ms.", to.timestamp)
+            }
+            Change::Recolored { from, to } => {
+                write!(f, "recolored {:?} to {:?}.", from.color, to.color)
+            }
+        }
+    }
+}
+
+/// Every change needed to turn `before` into `after`, ordered by time then lane.
+///
+/// Notes are only ever paired with notes of the same lane and kind, so turning a tap into a mine
+/// shows up as the tap being removed and the mine added.
+#[must_use]
+pub fn diff(before: &RuntimeChart, after: &RuntimeChart, options: &DiffOptions) -> Vec<Change> {
+    let mut before_lanes = by_lane(before);
+    let mut after_lanes = by_lane(after);
+    let mut changes = Vec::new();
+
+    for (key, before_notes) in &mut before_lanes {
+        let after_notes = after_lanes.entry(*key).or_default();
+
+        for (from, to) in pair_up(before_notes, after_notes, options.tolerance) {
+            if from.color != to.color {
+                changes.push(Change::Recolored { from, to });
+            }
+        }
+
+        for (from, to) in pair_up(before_notes, after_notes, options.max_shift) {
+            changes.push(Change::Moved { from, to });
+        }
+
+        changes.extend(
+            before_notes
+                .drain(..)
+                .cloned()
+                .map(|note| Change::Removed { note }),
+        );
+    }
+
+    changes.extend(
+        after_lanes
+            .into_values()
+            .flatten()
+            .cloned()
+            .map(|note| Change::Added { note }),
+    );
+
+    changes.sort_by_key(|change| (change.note().timestamp, change.note().lane));
+    changes
+}
+
+/// Notes grouped by lane and kind, each group sorted by time.
+fn by_lane(chart: &RuntimeChart) -> BTreeMap<(Lane, NoteKind), Vec<&RuntimeNote>> {
+    let mut lanes: BTreeMap<(Lane, NoteKind), Vec<&RuntimeNote>> = BTreeMap::new();
+    for note in &chart.notes {
+        lanes.entry((note.lane, note.kind)).or_default().push(note);
+    }
+    for notes in lanes.values_mut() {
+        notes.sort_by_key(|note| note.timestamp);
+    }
+    lanes
+}
+
+/// Pairs notes at most `window` milliseconds apart, removing them from both lists.
+fn pair_up(
+    before: &mut Vec<&RuntimeNote>,
+    after: &mut Vec<&RuntimeNote>,
+    window: u32,
+) -> Vec<(RuntimeNote, RuntimeNote)> {
+    let mut pairs = Vec::new();
+    let mut before_left = Vec::new();
+    let mut after_left = Vec::new();
+    let mut before_iter = std::mem::take(before).into_iter().peekable();
+    let mut after_iter = std::mem::take(after).into_iter().peekable();
+
+    loop {
+        match (before_iter.peek(), after_iter.peek()) {
+            (Some(from), Some(to)) if from.timestamp.abs_diff(to.timestamp) <= window => {
+                pairs.push(((*from).clone(), (*to).clone()));
+                before_iter.next();
+                after_iter.next();
+            }
+            (Some(from), Some(to)) if from.timestamp < to.timestamp => {
+                before_left.extend(before_iter.next());
+            }
+            (Some(_), Some(_)) => after_left.extend(after_iter.next()),
+            (Some(_), None) => before_left.extend(before_iter.next()),
+            (None, Some(_)) => after_left.extend(after_iter.next()),
+            (None, None) => break,
+        }
+    }
+
+    *before = before_left;
+    *after = after_left;
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NoteColor;
+
+    fn note(lane: u8, timestamp: u32, color: NoteColor) -> RuntimeNote {
+        RuntimeNote {
+            beat_position: timestamp * 30 / 1000,
+            color,
+            lane: Lane(lane),
+            timestamp,
+            kind: NoteKind::Tap,
+        }
+    }
+
+    fn before() -> RuntimeChart {
+        RuntimeChart::new(&[
+            note(0, 1000, NoteColor::Red),
+            note(1, 1500, NoteColor::Blue),
+            note(2, 2000, NoteColor::Red),
+            note(3, 2500, NoteColor::Blue),
+        ])
+    }
+
+    #[test]
+    pub fn test_identical_charts() {
+        assert!(diff(&before(), &before(), &DiffOptions::default()).is_empty());
+    }
+
+    #[test]
+    pub fn test_finds_changes() {
+        let after = RuntimeChart::new(&[
+            note(0, 1000, NoteColor::Red),
+            note(1, 1500, NoteColor::Yellow),
+            note(2, 2100, NoteColor::Red),
+            note(0, 3000, NoteColor::Blue),
+        ]);
+
+        let changes = diff(&before(), &after, &DiffOptions::default());
+        assert_eq!(
+            changes,
+            vec![
+                Change::Recolored {
+                    from: note(1, 1500, NoteColor::Blue),
+                    to: note(1, 1500, NoteColor::Yellow),
+                },
+                Change::Moved {
+                    from: note(2, 2000, NoteColor::Red),
+                    to: note(2, 2100, NoteColor::Red),
+                },
+                Change::Removed {
+                    note: note(3, 2500, NoteColor::Blue),
+                },
+                Change::Added {
+                    note: note(0, 3000, NoteColor::Blue),
+                },
+            ]
+        );
+        assert_eq!(
+            changes[1].to_string(),
+            "2000ms lane 2: moved +100ms to 2100ms."
+        );
+    }
+
+    #[test]
+    pub fn test_tolerance() {
+        let after = RuntimeChart::new(&[
+            note(0, 1005, NoteColor::Red),
+            note(1, 1495, NoteColor::Blue),
+            note(2, 2000, NoteColor::Red),
+            note(3, 2500, NoteColor::Blue),
+        ]);
+
+        let options = DiffOptions {
+            tolerance: 5,
+            ..DiffOptions::default()
+        };
+        assert!(diff(&before(), &after, &options).is_empty());
+        assert_eq!(diff(&before(), &after, &DiffOptions::default()).len(), 2);
+    }
+
+    #[test]
+    pub fn test_kind_change_is_remove_and_add() {
+        let mut mine = note(0, 1000, NoteColor::Red);
+        mine.kind = NoteKind::Mine;
+        let after = RuntimeChart::new(&[
+            mine,
+            note(1, 1500, NoteColor::Blue),
+            note(2, 2000, NoteColor::Red),
+            note(3, 2500, NoteColor::Blue),
+        ]);
+
+        assert!(matches!(
+            diff(&before(), &after, &DiffOptions::default()).as_slice(),
+            [Change::Removed { .. }, Change::Added { .. }]
+        ));
+    }
+}
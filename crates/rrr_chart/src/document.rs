@@ -0,0 +1,275 @@
+//! A plain text chart format meant to be diffed, edited by hand and generated by scripts.
+//!
+//! Documents are written as JSON or RON and share one schema:
+//!
+//! ```json
+//! {
+//!   "version": 2,
+//!   "metadata": { "title": "Song", "artist": "Artist", "author": "Stepper" },
+//!   "audio": "song.mp3",
+//!   "lanes": 4,
+//!   "timing": [{ "timestamp": 0, "micros_per_beat": 500000 }],
+//!   "notes": [
+//!     { "beat_position": 0, "color": "Red", "lane": 0, "timestamp": 0 }
+//!   ]
+//! }
+//! ```
+//!
+//! `audio` names a file next to the document and may be omitted, as may `metadata`, `timing`
+//! and `lanes` (which defaults to 4).
+//! `version` is bumped whenever the schema changes in a way older readers can't understand.
+//! Version 1 documents, which named 4K lanes by `direction`, are still read.
+
+use crate::{Metadata, RuntimeChart, RuntimeNote, TimingPoint};
+use rrr_types::LaneCount;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The newest document version this crate can read and the one it writes.
+pub const DOCUMENT_VERSION: u32 = 2;
+
+#[derive(Error, Debug)]
+pub enum DocumentError {
+    #[error("Unsupported document version {0}, expected at most {DOCUMENT_VERSION}.")]
+    Version(u32),
+
+    #[error("Document has no notes.")]
+    Empty,
+
+    #[error("Note at {timestamp}ms is in lane {lane}, but the chart only has {lanes}.")]
+    Lane {
+        timestamp: u32,
+        lane: u8,
+        lanes: LaneCount,
+    },
+
+    #[cfg(feature = "json")]
+    #[error("Invalid JSON document: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[cfg(feature = "ron")]
+    #[error("Invalid RON document: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+
+    #[cfg(feature = "ron")]
+    #[error("Could not write RON document: {0}")]
+    RonWrite(#[from] ron::Error),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocumentFormat {
+    #[cfg(feature = "json")]
+    Json,
+    #[cfg(feature = "ron")]
+    Ron,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChartDocument {
+    pub version: u32,
+    #[serde(default)]
+    pub metadata: Metadata,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio: Option<String>,
+    #[serde(default)]
+    pub lanes: LaneCount,
+    #[serde(default)]
+    pub timing: Vec<TimingPoint>,
+    pub notes: Vec<RuntimeNote>,
+}
+
+impl ChartDocument {
+    #[must_use]
+    pub fn new(chart: &RuntimeChart, metadata: &Metadata) -> Self {
+        Self {
+            version: DOCUMENT_VERSION,
+            metadata: metadata.clone(),
+            audio: None,
+            lanes: chart.lane_count,
+            timing: chart.timing.clone(),
+            notes: chart.notes.clone(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_audio(self, audio: &str) -> Self {
+        Self {
+            audio: Some(audio.to_string()),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn chart(&self) -> RuntimeChart {
+        RuntimeChart::new(&self.notes)
+            .with_timing(&self.timing)
+            .with_lane_count(self.lanes)
+    }
+
+    /// # Errors
+    ///
+    /// Will return [`DocumentError`] if `document` is not a valid document in `format`.
+    pub fn read(document: &str, format: DocumentFormat) -> Result<Self, DocumentError> {
+        let document: Self = match format {
+            #[cfg(feature = "json")]
+            DocumentFormat::Json => serde_json::from_str(document)?,
+            #[cfg(feature = "ron")]
+            DocumentFormat::Ron => ron::from_str(document)?,
+        };
+
+        if document.version > DOCUMENT_VERSION {
+            return Err(DocumentError::Version(document.version));
+        }
+
+        if document.notes.is_empty() {
+            return Err(DocumentError::Empty);
+        }
+
+        if let Some(note) = document
+            .notes
+            .iter()
+            .find(|note| !document.lanes.contains(note.lane))
+        {
+            return Err(DocumentError::Lane {
+                timestamp: note.timestamp,
+                lane: note.lane.0,
+                lanes: document.lanes,
+            });
+        }
+
+        Ok(document)
+    }
+
+    /// # Errors
+    ///
+    /// Will return [`DocumentError`] if the document can't be serialized.
+    pub fn write(&self, format: DocumentFormat) -> Result<String, DocumentError> {
+        Ok(match format {
+            #[cfg(feature = "json")]
+            DocumentFormat::Json => serde_json::to_string_pretty(self)?,
+            #[cfg(feature = "ron")]
+            DocumentFormat::Ron => {
+                ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NoteColor, NoteKind};
+    use rrr_types::Lane;
+
+    fn document() -> ChartDocument {
+        let chart = RuntimeChart::new(&[
+            RuntimeNote {
+                beat_position: 30,
+                color: NoteColor::Red,
+                lane: Lane(0),
+                timestamp: 1000,
+                kind: NoteKind::Tap,
+            },
+            RuntimeNote {
+                beat_position: 45,
+                color: NoteColor::Blue,
+                lane: Lane(3),
+                timestamp: 1500,
+                kind: NoteKind::Tap,
+            },
+        ])
+        .with_timing(&[TimingPoint::new(1000, 500_000)]);
+
+        let metadata = Metadata {
+            title: "Title".to_string(),
+            artist: "Artist".to_string(),
+            author: "Author".to_string(),
+        };
+
+        ChartDocument::new(&chart, &metadata).with_audio("song.mp3")
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_round_trip() -> Result<(), DocumentError> {
+        let document = document();
+        let json = document.write(DocumentFormat::Json)?;
+        assert_eq!(ChartDocument::read(&json, DocumentFormat::Json)?, document);
+        Ok(())
+    }
+
+    #[cfg(feature = "ron")]
+    #[test]
+    fn test_ron_round_trip() -> Result<(), DocumentError> {
+        let document = document();
+        let ron = document.write(DocumentFormat::Ron)?;
+        assert_eq!(ChartDocument::read(&ron, DocumentFormat::Ron)?, document);
+        Ok(())
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_read_minimal_json() -> Result<(), DocumentError> {
+        let json = r#"{
+            "version": 2,
+            "notes": [{ "beat_position": 0, "color": "Red", "lane": 2, "timestamp": 0 }]
+        }"#;
+        let document = ChartDocument::read(json, DocumentFormat::Json)?;
+        assert_eq!(document.metadata, Metadata::default());
+        assert_eq!(document.chart().notes.len(), 1);
+        Ok(())
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_read_note_kinds() -> Result<(), DocumentError> {
+        let json = r#"{
+            "version": 2,
+            "notes": [
+                { "beat_position": 0, "color": "Red", "lane": 0, "timestamp": 0, "kind": "mine" },
+                { "beat_position": 0, "color": "Red", "lane": 1, "timestamp": 0, "kind": "fake" }
+            ]
+        }"#;
+        let document = ChartDocument::read(json, DocumentFormat::Json)?;
+        assert_eq!(document.notes[0].kind, NoteKind::Mine);
+        assert_eq!(document.notes[1].kind, NoteKind::Fake);
+        Ok(())
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_read_version_1_directions() -> Result<(), DocumentError> {
+        let json = r#"{
+            "version": 1,
+            "notes": [{ "beat_position": 0, "color": "Red", "direction": "Up", "timestamp": 0 }]
+        }"#;
+        let document = ChartDocument::read(json, DocumentFormat::Json)?;
+        assert_eq!(document.notes[0].lane, Lane(2));
+        assert_eq!(document.lanes, LaneCount::FOUR);
+        Ok(())
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_reject_lane_outside_chart() {
+        let json = r#"{
+            "version": 2,
+            "lanes": 5,
+            "notes": [{ "beat_position": 0, "color": "Red", "lane": 5, "timestamp": 0 }]
+        }"#;
+        assert!(matches!(
+            ChartDocument::read(json, DocumentFormat::Json),
+            Err(DocumentError::Lane { lane: 5, .. })
+        ));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_reject_newer_version() {
+        let json = r#"{ "version": 3, "notes": [] }"#;
+        assert!(matches!(
+            ChartDocument::read(json, DocumentFormat::Json),
+            Err(DocumentError::Version(3))
+        ));
+    }
+}
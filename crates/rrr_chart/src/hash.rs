@@ -0,0 +1,146 @@
+//! A fingerprint of the notes a chart asks the player to hit.
+//!
+//! The hash only covers note data, so the same chart gives the same hash whether it was read
+//! from an swf, a beatmap or a document, and regardless of the audio it came with.
+
+use crate::{NoteKind, RuntimeChart, RuntimeNote};
+use rrr_types::LaneCount;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt::{Display, Formatter};
+
+/// Mixed into every hash, bump it if the encoding in [`RuntimeChart::content_hash`] changes.
+const HASH_DOMAIN: &[u8] = b"rrr-chart-v1";
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct ChartHash(pub [u8; 32]);
+
+impl Display for ChartHash {
+    /// Lowercase hex.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for byte in self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl RuntimeChart {
+    /// SHA-256 over the notes in a canonical order.
+    ///
+    /// Each note is written as its timestamp and beat position (little-endian `u32`),
+    /// then one byte each for its lane and color, after sorting by all four.
+    /// Charts with other than four lanes also hash their lane count first, and mines and fakes
+    /// are listed by position and kind after the notes, so charts of only 4K taps hash the same
+    /// as before those were introduced.
+    #[must_use]
+    pub fn content_hash(&self) -> ChartHash {
+        let mut notes: Vec<&RuntimeNote> = self.notes.iter().collect();
+        notes.sort_by_key(|note| {
+            (
+                note.timestamp,
+                note.beat_position,
+                note.lane,
+                note.color.to_byte(),
+                note.kind,
+            )
+        });
+
+        let mut hasher = Sha256::new();
+        hasher.update(HASH_DOMAIN);
+        if self.lane_count != LaneCount::FOUR {
+            hasher.update([self.lane_count.get()]);
+        }
+        hasher.update((notes.len() as u64).to_le_bytes());
+        for note in &notes {
+            hasher.update(note.timestamp.to_le_bytes());
+            hasher.update(note.beat_position.to_le_bytes());
+            hasher.update([note.lane.0, note.color.to_byte()]);
+        }
+        for (position, note) in notes.iter().enumerate() {
+            if note.kind != NoteKind::Tap {
+                hasher.update((position as u64).to_le_bytes());
+                hasher.update([note.kind.to_byte()]);
+            }
+        }
+
+        ChartHash(hasher.finalize().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NoteColor, TimingPoint};
+    use rrr_types::Lane;
+
+    fn notes() -> Vec<RuntimeNote> {
+        vec![
+            RuntimeNote {
+                beat_position: 30,
+                color: NoteColor::Red,
+                lane: Lane(0),
+                timestamp: 1000,
+                kind: NoteKind::Tap,
+            },
+            RuntimeNote {
+                beat_position: 45,
+                color: NoteColor::Blue,
+                lane: Lane(3),
+                timestamp: 1500,
+                kind: NoteKind::Tap,
+            },
+        ]
+    }
+
+    #[test]
+    pub fn test_hash_is_stable() {
+        // Changing this value invalidates every stored score, see `HASH_DOMAIN`.
+        assert_eq!(
+            RuntimeChart::new(&notes()).content_hash().to_string(),
+            "da6d6ab1df4acc3540f00fe71a49aed0c58ab2728a614b8e0feccc23765cf077"
+        );
+    }
+
+    #[test]
+    pub fn test_hash_ignores_order_and_timing() {
+        let mut reversed = notes();
+        reversed.reverse();
+
+        let chart = RuntimeChart::new(&notes());
+        let other = RuntimeChart::new(&reversed).with_timing(&[TimingPoint::new(0, 500_000)]);
+        assert_eq!(chart.content_hash(), other.content_hash());
+    }
+
+    #[test]
+    pub fn test_hash_includes_lane_count() {
+        let chart = RuntimeChart::new(&notes());
+        let seven = chart.clone().with_lane_count(LaneCount::new(7).unwrap());
+        assert_ne!(chart.content_hash(), seven.content_hash());
+    }
+
+    #[test]
+    pub fn test_hash_includes_kind() {
+        let mut mined = notes();
+        mined[1].kind = NoteKind::Mine;
+        let mut faked = notes();
+        faked[1].kind = NoteKind::Fake;
+
+        let hashes = [notes(), mined, faked].map(|notes| RuntimeChart::new(&notes).content_hash());
+        assert_ne!(hashes[0], hashes[1]);
+        assert_ne!(hashes[1], hashes[2]);
+    }
+
+    #[test]
+    pub fn test_hash_changes_with_notes() {
+        let mut recolored = notes();
+        recolored[0].color = NoteColor::Yellow;
+
+        assert_ne!(
+            RuntimeChart::new(&notes()).content_hash(),
+            RuntimeChart::new(&recolored).content_hash()
+        );
+    }
+}
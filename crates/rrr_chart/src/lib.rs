@@ -1,7 +1,28 @@
+mod audio;
 mod chart_impl;
+pub mod diff;
+#[cfg(feature = "serde")]
+mod document;
+mod hash;
+pub mod lint;
+mod metadata;
+#[cfg(feature = "bin")]
+pub mod native;
 mod note;
 mod parser;
+pub mod quantize;
+mod writer;
 
-pub use chart_impl::{Beat, RuntimeChart};
-pub use note::{Color as NoteColor, ColorIter, Note, NoteRow, RuntimeNote};
+pub use audio::{AudioFormat, AudioInfo};
+pub use chart_impl::{Beat, RuntimeChart, TimingPoint};
+#[cfg(feature = "serde")]
+pub use document::{ChartDocument, DocumentError, DocumentFormat, DOCUMENT_VERSION};
+pub use hash::ChartHash;
+pub use metadata::Metadata;
+pub use note::{Color as NoteColor, ColorIter, Note, NoteKind, NoteRow, RuntimeNote};
+#[cfg(feature = "osu")]
+pub use parser::osu;
+#[cfg(feature = "swf")]
 pub use parser::swf;
+#[cfg(feature = "sm")]
+pub use writer::sm;
@@ -0,0 +1,334 @@
+//! Finds mistakes in a chart that would make it unplayable or unfair.
+//!
+//! Notes are checked in time order whatever order the chart stores them in, swf charts are
+//! stored newest first. A chart that isn't stored in either order is still reported.
+
+use crate::{RuntimeChart, RuntimeNote};
+use std::fmt::{Display, Formatter};
+
+/// FFR positions notes on a 30 frames per second grid.
+const FRAMES_PER_SECOND: u64 = 30;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LintOptions {
+    /// How long the audio plays for in milliseconds, notes after it can't be hit.
+    pub audio_length: Option<u32>,
+    /// Notes on the same lane closer together than this many milliseconds can't be hit.
+    pub minijack_threshold: u32,
+    /// How many frames `beat_position` may drift from `timestamp` before it's reported.
+    pub beat_tolerance: u32,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        Self {
+            audio_length: None,
+            minijack_threshold: 50,
+            beat_tolerance: 1,
+        }
+    }
+}
+
+/// A problem with a single note, `index` is its position in [`RuntimeChart::notes`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Lint {
+    /// Shares its lane and timestamp with the note at `original`.
+    Duplicate {
+        index: usize,
+        original: usize,
+        note: RuntimeNote,
+    },
+
+    /// Breaks the order the rest of the chart is stored in, `previous` is the timestamp of the
+    /// note stored before it.
+    Unsorted {
+        index: usize,
+        note: RuntimeNote,
+        previous: u32,
+    },
+
+    PastAudio {
+        index: usize,
+        note: RuntimeNote,
+        audio_length: u32,
+    },
+
+    /// Follows the previous note on its lane by only `gap` milliseconds.
+    Minijack {
+        index: usize,
+        note: RuntimeNote,
+        gap: u32,
+    },
+
+    /// `beat_position` doesn't match the frame `timestamp` falls on.
+    BeatPosition {
+        index: usize,
+        note: RuntimeNote,
+        expected: u32,
+    },
+}
+
+impl Lint {
+    #[must_use]
+    pub fn index(&self) -> usize {
+        match self {
+            Lint::Duplicate { index, .. }
+            | Lint::Unsorted { index, .. }
+            | Lint::PastAudio { index, .. }
+            | Lint::Minijack { index, .. }
+            | Lint::BeatPosition { index, .. } => *index,
+        }
+    }
+
+    #[must_use]
+    pub fn note(&self) -> &RuntimeNote {
+        match self {
+            Lint::Duplicate { note, .. }
+            | Lint::Unsorted { note, .. }
+            | Lint::PastAudio { note, .. }
+            | Lint::Minijack { note, .. }
+            | Lint::BeatPosition { note, .. } => note,
+        }
+    }
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let note = self.note();
+        write!(
+            f,
+            "note {} ({} at {}ms): ",
+            self.index(),
+            note.lane,
+            note.timestamp
+        )?;
+
+        match self {
+            Lint::Duplicate { original, .. } => write!(f, "duplicate of note {original}."),
+            Lint::Unsorted { previous, .. } => {
+                write!(f, "is out of order after the note at {previous}ms.")
+            }
+            Lint::PastAudio { audio_length, .. } => {
+                write!(f, "is after the audio ends at {audio_length}ms.")
+            }
+            Lint::Minijack { gap, .. } => {
+                write!(f, "only {gap}ms after the previous note on this lane.")
+            }
+            Lint::BeatPosition { expected, .. } => write!(
+                f,
+                "beat position {} should be {expected}.",
+                note.beat_position
+            ),
+        }
+    }
+}
+
+/// Check every note in `chart`, results are ordered by note index.
+#[must_use]
+pub fn lint(chart: &RuntimeChart, options: &LintOptions) -> Vec<Lint> {
+    let notes = &chart.notes;
+    let mut lints = Vec::new();
+
+    let newest_first = match (notes.first(), notes.last()) {
+        (Some(first), Some(last)) => first.timestamp > last.timestamp,
+        _ => false,
+    };
+    for (index, pair) in notes.windows(2).enumerate() {
+        if let [previous, note] = pair {
+            let out_of_order = if newest_first {
+                note.timestamp > previous.timestamp
+            } else {
+                note.timestamp < previous.timestamp
+            };
+            if out_of_order {
+                lints.push(Lint::Unsorted {
+                    index: index + 1,
+                    note: note.clone(),
+                    previous: previous.timestamp,
+                });
+            }
+        }
+    }
+
+    // Mines and fakes are never hit, so they can't be duplicated or jacked.
+    let mut by_lane: Vec<(usize, &RuntimeNote)> = notes
+        .iter()
+        .enumerate()
+        .filter(|(_, note)| note.kind.is_judged())
+        .collect();
+    by_lane.sort_by_key(|(index, note)| (note.lane, note.timestamp, *index));
+    for pair in by_lane.windows(2) {
+        if let [(original, previous), (index, note)] = pair {
+            if previous.lane != note.lane {
+                continue;
+            }
+
+            let gap = note.timestamp - previous.timestamp;
+            if gap == 0 {
+                lints.push(Lint::Duplicate {
+                    index: *index,
+                    original: *original,
+                    note: (*note).clone(),
+                });
+            } else if gap < options.minijack_threshold {
+                lints.push(Lint::Minijack {
+                    index: *index,
+                    note: (*note).clone(),
+                    gap,
+                });
+            }
+        }
+    }
+
+    for (index, note) in notes.iter().enumerate() {
+        if let Some(audio_length) = options.audio_length {
+            if note.timestamp > audio_length {
+                lints.push(Lint::PastAudio {
+                    index,
+                    note: note.clone(),
+                    audio_length,
+                });
+            }
+        }
+
+        let expected = ms_to_frame(note.timestamp);
+        if note.beat_position.abs_diff(expected) > options.beat_tolerance {
+            lints.push(Lint::BeatPosition {
+                index,
+                note: note.clone(),
+                expected,
+            });
+        }
+    }
+
+    lints.sort_by_key(Lint::index);
+    lints
+}
+
+fn ms_to_frame(ms: u32) -> u32 {
+    (u64::from(ms) * FRAMES_PER_SECOND / 1000) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NoteColor, NoteKind};
+    use rrr_types::Direction;
+
+    fn note(direction: Direction, timestamp: u32) -> RuntimeNote {
+        RuntimeNote {
+            beat_position: ms_to_frame(timestamp),
+            color: NoteColor::Blue,
+            lane: direction.into(),
+            timestamp,
+            kind: NoteKind::Tap,
+        }
+    }
+
+    #[test]
+    pub fn test_clean_chart() {
+        let chart = RuntimeChart::new(&[
+            note(Direction::Left, 1000),
+            note(Direction::Right, 1000),
+            note(Direction::Left, 1500),
+        ]);
+        assert!(lint(&chart, &LintOptions::default()).is_empty());
+    }
+
+    #[test]
+    pub fn test_newest_first_chart() {
+        let chart = RuntimeChart::new(&[
+            note(Direction::Left, 1500),
+            note(Direction::Right, 1000),
+            note(Direction::Left, 1000),
+            note(Direction::Left, 1000),
+        ]);
+
+        assert!(matches!(
+            lint(&chart, &LintOptions::default()).as_slice(),
+            [Lint::Duplicate {
+                index: 3,
+                original: 2,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    pub fn test_finds_problems() {
+        let mut drifted = note(Direction::Up, 3000);
+        drifted.beat_position = 60;
+
+        let chart = RuntimeChart::new(&[
+            note(Direction::Left, 1000),
+            note(Direction::Left, 1000),
+            note(Direction::Left, 1020),
+            note(Direction::Down, 500),
+            drifted,
+            note(Direction::Right, 9000),
+        ]);
+
+        let options = LintOptions {
+            audio_length: Some(5000),
+            ..LintOptions::default()
+        };
+        let lints = lint(&chart, &options);
+
+        assert!(matches!(
+            lints.as_slice(),
+            [
+                Lint::Duplicate {
+                    index: 1,
+                    original: 0,
+                    ..
+                },
+                Lint::Minijack {
+                    index: 2,
+                    gap: 20,
+                    ..
+                },
+                Lint::Unsorted {
+                    index: 3,
+                    previous: 1020,
+                    ..
+                },
+                Lint::BeatPosition {
+                    index: 4,
+                    expected: 90,
+                    ..
+                },
+                Lint::PastAudio { index: 5, .. },
+            ]
+        ));
+    }
+
+    #[test]
+    pub fn test_unsorted_chart() {
+        let chart = RuntimeChart::new(&[
+            note(Direction::Left, 1500),
+            note(Direction::Right, 1000),
+            note(Direction::Down, 1200),
+            note(Direction::Up, 500),
+        ]);
+
+        assert!(matches!(
+            lint(&chart, &LintOptions::default()).as_slice(),
+            [Lint::Unsorted {
+                index: 2,
+                previous: 1000,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    pub fn test_ignores_unjudged_notes() {
+        let mut mine = note(Direction::Left, 1000);
+        mine.kind = NoteKind::Mine;
+        let mut fake = note(Direction::Left, 1020);
+        fake.kind = NoteKind::Fake;
+
+        let chart = RuntimeChart::new(&[note(Direction::Left, 1000), mine, fake]);
+        assert!(lint(&chart, &LintOptions::default()).is_empty());
+    }
+}
@@ -0,0 +1,12 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Descriptive information about a chart that isn't needed to play it.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Default, Eq, PartialEq, Hash, Debug)]
+pub struct Metadata {
+    pub title: String,
+    pub artist: String,
+    /// Who stepped the chart.
+    pub author: String,
+}
@@ -0,0 +1,338 @@
+//! The native binary chart format (`.rrr`), a single file holding the chart, its metadata and
+//! its audio.
+//!
+//! Everything is little-endian. After the `RRRC` magic and a `u16` version come the metadata
+//! strings, the lane count, timing points, notes, then the audio description and audio bytes.
+//! Strings and lists are prefixed by their length as a `u32`.
+
+use crate::{
+    AudioFormat, AudioInfo, Metadata, NoteColor, NoteKind, RuntimeChart, RuntimeNote, TimingPoint,
+};
+use rrr_types::{Lane, LaneCount};
+use thiserror::Error;
+
+const MAGIC: &[u8; 4] = b"RRRC";
+
+/// The newest version this crate can read and the one it writes.
+pub const NATIVE_VERSION: u16 = 1;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum NativeError {
+    #[error("Not a native chart file.")]
+    Magic,
+
+    #[error("Unsupported native chart version {0}, expected at most {NATIVE_VERSION}.")]
+    Version(u16),
+
+    #[error("Chart file ends early, at byte {0}.")]
+    Truncated(usize),
+
+    #[error("Invalid {field} at byte {position}.")]
+    Invalid {
+        field: &'static str,
+        position: usize,
+    },
+
+    #[error("Note at {timestamp}ms is in lane {lane}, but the chart only has {lanes}.")]
+    Lane {
+        timestamp: u32,
+        lane: u8,
+        lanes: LaneCount,
+    },
+
+    #[error("Chart has no notes.")]
+    Empty,
+}
+
+pub struct Parsed {
+    pub chart: RuntimeChart,
+    pub metadata: Metadata,
+    pub audio: Vec<u8>,
+    pub audio_info: Option<AudioInfo>,
+}
+
+/// Writes a native chart file.
+#[must_use]
+pub fn write(
+    chart: &RuntimeChart,
+    metadata: &Metadata,
+    audio: &[u8],
+    audio_info: Option<&AudioInfo>,
+) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&NATIVE_VERSION.to_le_bytes());
+
+    for text in [&metadata.title, &metadata.artist, &metadata.author] {
+        write_len(&mut out, text.len());
+        out.extend_from_slice(text.as_bytes());
+    }
+
+    out.push(chart.lane_count.get());
+
+    write_len(&mut out, chart.timing.len());
+    for timing_point in &chart.timing {
+        out.extend_from_slice(&timing_point.timestamp.to_le_bytes());
+        out.extend_from_slice(&timing_point.micros_per_beat.to_le_bytes());
+    }
+
+    write_len(&mut out, chart.notes.len());
+    for note in &chart.notes {
+        out.extend_from_slice(&note.timestamp.to_le_bytes());
+        out.extend_from_slice(&note.beat_position.to_le_bytes());
+        out.extend_from_slice(&[note.lane.0, note.color.to_byte(), note.kind.to_byte()]);
+    }
+
+    match audio_info {
+        Some(info) => {
+            out.push(1);
+            out.push(format_byte(info.format));
+            out.extend_from_slice(&info.sample_rate.to_le_bytes());
+            out.extend_from_slice(&[u8::from(info.is_stereo), u8::from(info.is_16_bit)]);
+            out.extend_from_slice(&info.latency_seek.to_le_bytes());
+        }
+        None => out.push(0),
+    }
+
+    write_len(&mut out, audio.len());
+    out.extend_from_slice(audio);
+
+    out
+}
+
+/// Reads a native chart file.
+///
+/// # Errors
+///
+/// Will return [`NativeError`] if `data` is not a valid native chart file.
+pub fn parse(data: &[u8]) -> Result<Parsed, NativeError> {
+    let mut reader = Reader { data, position: 0 };
+
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err(NativeError::Magic);
+    }
+
+    let version = u16::from_le_bytes(reader.array()?);
+    if version > NATIVE_VERSION {
+        return Err(NativeError::Version(version));
+    }
+
+    let metadata = Metadata {
+        title: reader.string("title")?,
+        artist: reader.string("artist")?,
+        author: reader.string("author")?,
+    };
+
+    let lanes_position = reader.position;
+    let lanes = LaneCount::new(reader.byte()?).ok_or(NativeError::Invalid {
+        field: "lane count",
+        position: lanes_position,
+    })?;
+
+    let timing_count = reader.length()?;
+    let mut timing = Vec::new();
+    for _ in 0..timing_count {
+        timing.push(TimingPoint::new(
+            i32::from_le_bytes(reader.array()?),
+            u32::from_le_bytes(reader.array()?),
+        ));
+    }
+
+    let note_count = reader.length()?;
+    if note_count == 0 {
+        return Err(NativeError::Empty);
+    }
+
+    let mut notes = Vec::new();
+    for _ in 0..note_count {
+        let timestamp = u32::from_le_bytes(reader.array()?);
+        let beat_position = u32::from_le_bytes(reader.array()?);
+        let lane = Lane(reader.byte()?);
+        if !lanes.contains(lane) {
+            return Err(NativeError::Lane {
+                timestamp,
+                lane: lane.0,
+                lanes,
+            });
+        }
+        let color = reader.decode("note color", NoteColor::from_byte)?;
+        let kind = reader.decode("note kind", NoteKind::from_byte)?;
+
+        notes.push(RuntimeNote {
+            beat_position,
+            color,
+            lane,
+            timestamp,
+            kind,
+        });
+    }
+
+    let audio_info = match reader.byte()? {
+        0 => None,
+        _ => Some(AudioInfo {
+            format: reader.decode("audio format", format_from_byte)?,
+            sample_rate: u32::from_le_bytes(reader.array()?),
+            is_stereo: reader.byte()? != 0,
+            is_16_bit: reader.byte()? != 0,
+            latency_seek: i16::from_le_bytes(reader.array()?),
+        }),
+    };
+
+    let audio_len = reader.length()?;
+    let audio = reader.take(audio_len)?.to_vec();
+
+    Ok(Parsed {
+        chart: RuntimeChart::new(&notes)
+            .with_timing(&timing)
+            .with_lane_count(lanes),
+        metadata,
+        audio,
+        audio_info,
+    })
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) {
+    let len = u32::try_from(len).unwrap_or(u32::MAX);
+    out.extend_from_slice(&len.to_le_bytes());
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], NativeError> {
+        let bytes = self
+            .position
+            .checked_add(len)
+            .and_then(|end| self.data.get(self.position..end))
+            .ok_or(NativeError::Truncated(self.position))?;
+        self.position += len;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], NativeError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn byte(&mut self) -> Result<u8, NativeError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn length(&mut self) -> Result<usize, NativeError> {
+        let position = self.position;
+        usize::try_from(u32::from_le_bytes(self.array()?)).map_err(|_| NativeError::Invalid {
+            field: "length",
+            position,
+        })
+    }
+
+    fn string(&mut self, field: &'static str) -> Result<String, NativeError> {
+        let len = self.length()?;
+        let position = self.position;
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|_| NativeError::Invalid { field, position })
+    }
+
+    fn decode<T>(
+        &mut self,
+        field: &'static str,
+        from_byte: impl FnOnce(u8) -> Option<T>,
+    ) -> Result<T, NativeError> {
+        let position = self.position;
+        from_byte(self.byte()?).ok_or(NativeError::Invalid { field, position })
+    }
+}
+
+fn format_byte(format: AudioFormat) -> u8 {
+    match format {
+        AudioFormat::Mp3 => 0,
+        AudioFormat::Adpcm => 1,
+        AudioFormat::Pcm => 2,
+        AudioFormat::PcmNativeEndian => 3,
+        AudioFormat::Nellymoser => 4,
+        AudioFormat::Speex => 5,
+        AudioFormat::Aac => 6,
+    }
+}
+
+fn format_from_byte(byte: u8) -> Option<AudioFormat> {
+    Some(match byte {
+        0 => AudioFormat::Mp3,
+        1 => AudioFormat::Adpcm,
+        2 => AudioFormat::Pcm,
+        3 => AudioFormat::PcmNativeEndian,
+        4 => AudioFormat::Nellymoser,
+        5 => AudioFormat::Speex,
+        6 => AudioFormat::Aac,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chart() -> RuntimeChart {
+        RuntimeChart::new(&[
+            RuntimeNote {
+                beat_position: 30,
+                color: NoteColor::Red,
+                lane: Lane(0),
+                timestamp: 1000,
+                kind: NoteKind::Tap,
+            },
+            RuntimeNote {
+                beat_position: 45,
+                color: NoteColor::Blue,
+                lane: Lane(4),
+                timestamp: 1500,
+                kind: NoteKind::Mine,
+            },
+        ])
+        .with_timing(&[TimingPoint::new(-20, 500_000)])
+        .with_lane_count(LaneCount::new(5).unwrap())
+    }
+
+    #[test]
+    pub fn test_round_trip() -> Result<(), NativeError> {
+        let metadata = Metadata {
+            title: "Title".to_string(),
+            artist: "Artíst".to_string(),
+            author: "Author".to_string(),
+        };
+        let audio_info = AudioInfo {
+            format: AudioFormat::Aac,
+            sample_rate: 44100,
+            is_stereo: true,
+            latency_seek: -12,
+            ..AudioInfo::default()
+        };
+
+        let data = write(&chart(), &metadata, &[1, 2, 3], Some(&audio_info));
+        let parsed = parse(&data)?;
+
+        assert_eq!(parsed.chart, chart());
+        assert_eq!(parsed.metadata, metadata);
+        assert_eq!(parsed.audio, vec![1, 2, 3]);
+        assert_eq!(parsed.audio_info, Some(audio_info));
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_reject_truncated() {
+        let data = write(&chart(), &Metadata::default(), &[1, 2, 3], None);
+        for len in 0..data.len() {
+            assert!(parse(&data[..len]).is_err(), "{len} bytes parsed");
+        }
+    }
+
+    #[test]
+    pub fn test_reject_bad_header() {
+        assert_eq!(parse(b"RIFF\x01\x00").err(), Some(NativeError::Magic));
+        assert_eq!(parse(b"RRRC\x02\x00").err(), Some(NativeError::Version(2)));
+    }
+}
@@ -1,4 +1,4 @@
-use rrr_types::Direction;
+use rrr_types::Lane;
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 use strum::{EnumCount, EnumIter};
@@ -31,22 +31,100 @@ pub enum Color {
     Receptor,
 }
 
+/// How a note is played.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[derive(Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum NoteKind {
+    /// Pressed when it reaches the receptor.
+    #[default]
+    Tap,
+    /// Must not be pressed, pressing its lane while it passes the receptor is penalized.
+    Mine,
+    /// Drawn like a tap but never judged.
+    Fake,
+}
+
+impl NoteKind {
+    /// Whether the player is scored on hitting this note.
+    #[must_use]
+    pub fn is_judged(self) -> bool {
+        self == NoteKind::Tap
+    }
+}
+
+// Byte values are spelled out rather than cast so reordering the enums can't change
+// existing hashes or files.
+impl Color {
+    pub(crate) fn to_byte(self) -> u8 {
+        match self {
+            Color::Red => 0,
+            Color::Yellow => 1,
+            Color::Blue => 2,
+            Color::Orange => 3,
+            Color::Green => 4,
+            Color::Pink => 5,
+            Color::Purple => 6,
+            Color::Cyan => 7,
+            Color::White => 8,
+            Color::Receptor => 9,
+        }
+    }
+
+    #[cfg_attr(not(feature = "bin"), allow(dead_code))]
+    pub(crate) fn from_byte(byte: u8) -> Option<Self> {
+        Some(match byte {
+            0 => Color::Red,
+            1 => Color::Yellow,
+            2 => Color::Blue,
+            3 => Color::Orange,
+            4 => Color::Green,
+            5 => Color::Pink,
+            6 => Color::Purple,
+            7 => Color::Cyan,
+            8 => Color::White,
+            9 => Color::Receptor,
+            _ => return None,
+        })
+    }
+}
+
+impl NoteKind {
+    pub(crate) fn to_byte(self) -> u8 {
+        match self {
+            NoteKind::Tap => 0,
+            NoteKind::Mine => 1,
+            NoteKind::Fake => 2,
+        }
+    }
+
+    #[cfg_attr(not(feature = "bin"), allow(dead_code))]
+    pub(crate) fn from_byte(byte: u8) -> Option<Self> {
+        Some(match byte {
+            0 => NoteKind::Tap,
+            1 => NoteKind::Mine,
+            2 => NoteKind::Fake,
+            _ => return None,
+        })
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
 pub struct RuntimeNote {
     pub beat_position: u32,
     pub color: Color,
-    pub direction: Direction,
+    /// Older documents call this `direction`.
+    #[cfg_attr(feature = "serde", serde(alias = "direction"))]
+    pub lane: Lane,
     pub timestamp: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub kind: NoteKind,
 }
 
 impl Display for RuntimeNote {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{:?} {:?} {}",
-            self.color, self.direction, self.beat_position
-        )
+        write!(f, "{:?} {} {}", self.color, self.lane, self.beat_position)
     }
 }
 
@@ -66,11 +144,15 @@ impl PartialOrd for RuntimeNote {
             Some(core::cmp::Ordering::Equal) => {}
             ord => return ord,
         }
-        match self.direction.partial_cmp(&other.direction) {
+        match self.lane.partial_cmp(&other.lane) {
+            Some(core::cmp::Ordering::Equal) => {}
+            ord => return ord,
+        }
+        match self.timestamp.partial_cmp(&other.timestamp) {
             Some(core::cmp::Ordering::Equal) => {}
             ord => return ord,
         }
-        self.timestamp.partial_cmp(&other.timestamp)
+        self.kind.partial_cmp(&other.kind)
     }
 }
 
@@ -1 +1,4 @@
+#[cfg(feature = "osu")]
+pub mod osu;
+#[cfg(feature = "swf")]
 pub mod swf;
@@ -0,0 +1,323 @@
+//! Reads osu!mania beatmaps (`.osu`).
+//!
+//! 4K to 8K maps are supported. Hold notes are imported as taps on their head.
+//! Notes are colored by [quantization](crate::quantize), maps without timing points stay blue.
+
+use crate::{chart_impl::RuntimeChart, Metadata, NoteColor, NoteKind, RuntimeNote, TimingPoint};
+use rrr_types::{Lane, LaneCount};
+use thiserror::Error;
+
+/// The width of the osu! playfield, used to map an `x` position to a column.
+const PLAYFIELD_WIDTH: i64 = 512;
+const MANIA_MODE: i64 = 3;
+
+#[derive(Error, Debug)]
+pub enum OsuParseError {
+    #[error("Missing osu file format header.")]
+    Header,
+
+    #[error("Unsupported game mode {0}, only osu!mania is supported.")]
+    Mode(i64),
+
+    #[error("Unsupported key count {0}, only 4K to 8K are supported.")]
+    KeyCount(u32),
+
+    #[error("Invalid {section} entry on line {line}.")]
+    Line { section: &'static str, line: usize },
+
+    #[error("No hit objects in beatmap.")]
+    Empty,
+}
+
+pub struct Parsed {
+    pub chart: RuntimeChart,
+    pub metadata: Metadata,
+    pub audio_filename: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Header,
+    General,
+    Metadata,
+    Difficulty,
+    TimingPoints,
+    HitObjects,
+    Other,
+}
+
+impl Section {
+    fn from_header(line: &str) -> Option<Self> {
+        let name = line.strip_prefix('[')?.strip_suffix(']')?;
+        Some(match name {
+            "General" => Section::General,
+            "Metadata" => Section::Metadata,
+            "Difficulty" => Section::Difficulty,
+            "TimingPoints" => Section::TimingPoints,
+            "HitObjects" => Section::HitObjects,
+            _ => Section::Other,
+        })
+    }
+}
+
+/// Parse a full beatmap.
+///
+/// # Errors
+///
+/// Will return [`OsuParseError`] if the beatmap is not a 4K to 8K osu!mania map or is malformed.
+pub fn parse(osu: &str) -> Result<Parsed, OsuParseError> {
+    let mut section = Section::Header;
+    let mut mode = 0;
+    let mut key_count = 0;
+    let mut metadata = Metadata::default();
+    let mut audio_filename = None;
+    let mut timing = Vec::new();
+    // Hit objects are kept as (x, time) until the key count is known.
+    let mut hit_objects = Vec::new();
+
+    for (index, raw_line) in osu.lines().enumerate() {
+        let line = raw_line.trim_start_matches('\u{feff}').trim();
+        let line_number = index.saturating_add(1);
+
+        if section == Section::Header {
+            if line.is_empty() {
+                continue;
+            }
+            if !line.starts_with("osu file format") {
+                return Err(OsuParseError::Header);
+            }
+            section = Section::Other;
+            continue;
+        }
+
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+
+        if let Some(next_section) = Section::from_header(line) {
+            section = next_section;
+            continue;
+        }
+
+        match section {
+            Section::General => match key_value(line) {
+                Some(("Mode", value)) => {
+                    mode = value.parse().map_err(|_| OsuParseError::Line {
+                        section: "General",
+                        line: line_number,
+                    })?;
+                }
+                Some(("AudioFilename", value)) => audio_filename = Some(value.to_string()),
+                _ => {}
+            },
+
+            Section::Metadata => match key_value(line) {
+                Some(("Title", value)) => metadata.title = value.to_string(),
+                Some(("Artist", value)) => metadata.artist = value.to_string(),
+                Some(("Creator", value)) => metadata.author = value.to_string(),
+                _ => {}
+            },
+
+            Section::Difficulty => {
+                if let Some(("CircleSize", value)) = key_value(line) {
+                    let circle_size: f32 = value.parse().map_err(|_| OsuParseError::Line {
+                        section: "Difficulty",
+                        line: line_number,
+                    })?;
+                    key_count = circle_size.round() as u32;
+                }
+            }
+
+            Section::TimingPoints => {
+                if let Some(timing_point) = parse_timing_point(line).ok_or(OsuParseError::Line {
+                    section: "TimingPoints",
+                    line: line_number,
+                })? {
+                    timing.push(timing_point);
+                }
+            }
+
+            Section::HitObjects => {
+                let hit_object = parse_hit_object(line).ok_or(OsuParseError::Line {
+                    section: "HitObjects",
+                    line: line_number,
+                })?;
+                hit_objects.push(hit_object);
+            }
+
+            Section::Header | Section::Other => {}
+        }
+    }
+
+    if mode != MANIA_MODE {
+        return Err(OsuParseError::Mode(mode));
+    }
+
+    let lane_count = u8::try_from(key_count)
+        .ok()
+        .and_then(LaneCount::new)
+        .ok_or(OsuParseError::KeyCount(key_count))?;
+
+    if hit_objects.is_empty() {
+        return Err(OsuParseError::Empty);
+    }
+
+    let mut notes: Vec<RuntimeNote> = hit_objects
+        .into_iter()
+        .map(|(x, timestamp)| RuntimeNote {
+            beat_position: ms_to_frame(timestamp),
+            color: NoteColor::Blue,
+            lane: lane_from_x(x, lane_count),
+            timestamp,
+            kind: NoteKind::Tap,
+        })
+        .collect();
+    notes.sort_by_key(|note| (note.timestamp, note.lane));
+    timing.sort_by_key(|timing_point| timing_point.timestamp);
+
+    Ok(Parsed {
+        chart: RuntimeChart::new(&notes)
+            .with_timing(&timing)
+            .with_lane_count(lane_count)
+            .with_quantized_colors(),
+        metadata,
+        audio_filename,
+    })
+}
+
+/// Find the audio file the beatmap plays, relative to the `.osu` file.
+#[must_use]
+pub fn audio_filename(osu: &str) -> Option<String> {
+    osu.lines()
+        .filter_map(|line| key_value(line.trim()))
+        .find(|(key, _)| *key == "AudioFilename")
+        .map(|(_, value)| value.to_string())
+}
+
+fn key_value(line: &str) -> Option<(&str, &str)> {
+    line.split_once(':')
+        .map(|(key, value)| (key.trim(), value.trim()))
+}
+
+/// Returns `Some(None)` for inherited (scroll velocity) timing points, which don't change tempo.
+fn parse_timing_point(line: &str) -> Option<Option<TimingPoint>> {
+    let mut fields = line.split(',').map(str::trim);
+    let time: f64 = fields.next()?.parse().ok()?;
+    let beat_length: f64 = fields.next()?.parse().ok()?;
+    let uninherited = fields.nth(4) != Some("0");
+
+    if !uninherited || beat_length <= 0. {
+        return Some(None);
+    }
+
+    Some(Some(TimingPoint::new(
+        time.round() as i32,
+        (beat_length * 1000.).round() as u32,
+    )))
+}
+
+/// Returns the `x` position and time of a hit object.
+fn parse_hit_object(line: &str) -> Option<(i64, u32)> {
+    let mut fields = line.split(',').map(str::trim);
+    let x: i64 = fields.next()?.parse().ok()?;
+    let _y = fields.next()?;
+    let time: i64 = fields.next()?.parse().ok()?;
+
+    Some((x, u32::try_from(time).ok()?))
+}
+
+fn lane_from_x(x: i64, lane_count: LaneCount) -> Lane {
+    let lanes = i64::from(lane_count.get());
+    let column = x.clamp(0, PLAYFIELD_WIDTH - 1) * lanes / PLAYFIELD_WIDTH;
+    Lane(column as u8)
+}
+
+/// FFR positions notes on a 30 frames per second grid.
+fn ms_to_frame(ms: u32) -> u32 {
+    (u64::from(ms) * 30 / 1000) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_parse_4k_beatmap() -> Result<(), OsuParseError> {
+        let osu = include_str!("./test_assets/test_4k.osu");
+        let parsed = parse(osu)?;
+
+        assert_eq!(parsed.audio_filename.as_deref(), Some("audio.mp3"));
+        assert_eq!(parsed.metadata.title, "Test Song");
+        assert_eq!(parsed.metadata.artist, "Test Artist");
+        assert_eq!(parsed.metadata.author, "Test Mapper");
+
+        let notes = &parsed.chart.notes;
+        assert_eq!(notes.len(), 6);
+        assert_eq!(parsed.chart.lane_count, LaneCount::FOUR);
+        assert_eq!(notes[0].lane, Lane(0));
+        assert_eq!(notes[1].lane, Lane(1));
+        assert_eq!(notes[2].lane, Lane(2));
+        assert_eq!(notes[3].lane, Lane(3));
+        assert_eq!(notes[3].timestamp, 1230);
+        assert_eq!(notes[3].beat_position, 36);
+        assert_eq!(notes[0].color, NoteColor::Red);
+        assert_eq!(notes[1].color, NoteColor::Blue);
+        assert_eq!(notes[4].color, NoteColor::Red);
+
+        // The inherited timing point is skipped.
+        assert_eq!(
+            parsed.chart.timing,
+            vec![
+                TimingPoint::new(-20, 500_000),
+                TimingPoint::new(2980, 375_000)
+            ]
+        );
+        assert!((parsed.chart.timing[0].bpm() - 120.).abs() < f64::EPSILON);
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_audio_filename() {
+        let osu = include_str!("./test_assets/test_4k.osu");
+        assert_eq!(audio_filename(osu).as_deref(), Some("audio.mp3"));
+    }
+
+    #[test]
+    pub fn test_reject_non_mania() {
+        let osu = "osu file format v14\n\n[General]\nMode: 0\n\n[HitObjects]\n64,192,480,1,0\n";
+        assert!(matches!(parse(osu), Err(OsuParseError::Mode(0))));
+    }
+
+    #[test]
+    pub fn test_parse_7k() -> Result<(), OsuParseError> {
+        let osu = "osu file format v14\n\n[General]\nMode: 3\n\n[Difficulty]\nCircleSize:7\n\n[HitObjects]\n36,192,480,1,0\n475,192,480,1,0\n";
+        let parsed = parse(osu)?;
+        assert_eq!(parsed.chart.lane_count.get(), 7);
+        assert_eq!(parsed.chart.notes[0].lane, Lane(0));
+        assert_eq!(parsed.chart.notes[1].lane, Lane(6));
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_clamp_offscreen_notes() -> Result<(), OsuParseError> {
+        let osu = "osu file format v14\n\n[General]\nMode: 3\n\n[Difficulty]\nCircleSize:4\n\n[HitObjects]\n-64,192,480,1,0\n9223372036854775807,192,960,1,0\n";
+        let parsed = parse(osu)?;
+        assert_eq!(parsed.chart.notes[0].lane, Lane(0));
+        assert_eq!(parsed.chart.notes[1].lane, Lane(3));
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_reject_10k() {
+        let osu = "osu file format v14\n\n[General]\nMode: 3\n\n[Difficulty]\nCircleSize:10\n\n[HitObjects]\n64,192,480,1,0\n";
+        assert!(matches!(parse(osu), Err(OsuParseError::KeyCount(10))));
+    }
+
+    #[test]
+    pub fn test_reject_missing_header() {
+        assert!(matches!(
+            parse("[General]\nMode: 3\n"),
+            Err(OsuParseError::Header)
+        ));
+    }
+}
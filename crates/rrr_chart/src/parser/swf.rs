@@ -1,4 +1,6 @@
-use crate::{chart_impl::RuntimeChart, NoteColor, RuntimeNote};
+use crate::{
+    chart_impl::RuntimeChart, AudioFormat, AudioInfo, Metadata, NoteColor, NoteKind, RuntimeNote,
+};
 use ::swf::{
     avm1::{
         self,
@@ -8,23 +10,71 @@ use ::swf::{
     SwfBuf, UTF_8,
 };
 use rrr_types::Direction;
-use std::ops::ControlFlow;
+use std::{
+    fmt::{Display, Formatter},
+    ops::ControlFlow,
+};
 use swf::SwfStr;
 use thiserror::Error;
 
-#[derive(Error, Debug)]
-enum ChartParseError {
-    #[error("Invalid beat position in chart.")]
-    BeatPosition,
+/// Where in the decompressed swf a problem was found.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Location {
+    /// Index of the tag, counting from the first tag after the header.
+    pub tag: usize,
+    /// Offset of the start of the tag in the decompressed swf body.
+    pub byte: usize,
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "tag {} (byte {})", self.tag, self.byte)
+    }
+}
+
+/// Everything that can go wrong while reading a chart out of an swf.
+///
+/// Errors from the `swf` crate are kept as their message, since they aren't `Send`.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ChartParseError {
+    #[error("Could not decompress swf: {0}")]
+    Decompression(String),
+
+    #[error("Swf claims to be {0} bytes, which is more than any chart needs.")]
+    TooLarge(u32),
+
+    #[error("Malformed tag at {location}: {message}")]
+    Tag { location: Location, message: String },
 
-    #[error("Invalid direction in chart.")]
-    NoteDirection,
+    #[error("Malformed action at {location}: {message}")]
+    Action { location: Location, message: String },
 
-    #[error("Invalid note color in chart.")]
-    NoteColor,
+    #[error("Unexpected action {action} in chart data at {location}.")]
+    UnexpectedAction { location: Location, action: String },
 
-    #[error("Invalid timestamp in chart.")]
-    Timestamp,
+    #[error("Constant pool index {index} is out of bounds at {location}.")]
+    ConstantPool { location: Location, index: u16 },
+
+    #[error("Truncated audio block at {location}.")]
+    TruncatedAudio { location: Location },
+
+    #[error("No chart data in swf.")]
+    MissingChart,
+
+    #[error("Invalid note array length at {location}.")]
+    NoteLength { location: Location },
+
+    #[error("Invalid beat position in chart at {location}.")]
+    BeatPosition { location: Location },
+
+    #[error("Invalid direction in chart at {location}.")]
+    NoteDirection { location: Location },
+
+    #[error("Invalid note color in chart at {location}.")]
+    NoteColor { location: Location },
+
+    #[error("Invalid timestamp in chart at {location}.")]
+    Timestamp { location: Location },
 }
 
 pub struct SwfParser<S: SwfParserState> {
@@ -41,21 +91,37 @@ pub struct ReadyToParse {
 
 pub struct Parsing {
     stream: SwfBuf,
+    /// How far into `stream.data` the previous tick got.
+    position: usize,
+    tags_read: usize,
     mp3: Vec<u8>,
+    audio: Option<AudioInfo>,
+    metadata: Metadata,
     chart: Vec<RuntimeNote>,
 }
 
 #[allow(unused)]
 pub struct Parsed {
     pub mp3: Vec<u8>,
+    /// Taken from the `SoundStreamHead`, or the `DefineSound` if the swf has no stream.
+    pub audio: Option<AudioInfo>,
+    /// Taken from the swf's XMP metadata, empty for most FFR charts and never naming the artist.
+    pub metadata: Metadata,
     pub chart: RuntimeChart,
 }
 
 pub enum ParsingState {
-    Parsing,
+    /// Percentage of the swf that has been read.
+    Parsing(f32),
     Finished,
 }
 
+/// Roughly how many bytes of tags [`SwfParser::tick`] reads before handing control back.
+pub const DEFAULT_TICK_BUDGET: usize = 64 * 1024;
+
+/// The largest decompressed size we accept, the `swf` crate allocates whatever the header claims.
+pub const MAX_SWF_SIZE: u32 = 64 * 1024 * 1024;
+
 pub trait SwfParserState {}
 impl SwfParserState for Compressed {}
 impl SwfParserState for ReadyToParse {}
@@ -72,9 +138,24 @@ impl SwfParser<Compressed> {
 
     /// # Errors
     ///
-    /// Will return `swf::error::Error` if `swf_file` is not a valid swf binary slice.
-    pub fn decompress(self) -> anyhow::Result<SwfParser<ReadyToParse>, swf::error::Error> {
-        let stream = swf::decompress_swf(self.state.raw_swf.as_slice())?;
+    /// Will return [`ChartParseError::Decompression`] if `swf_file` is not a valid swf binary slice,
+    /// or [`ChartParseError::TooLarge`] if it claims to be bigger than [`MAX_SWF_SIZE`].
+    pub fn decompress(self) -> Result<SwfParser<ReadyToParse>, ChartParseError> {
+        let raw_swf = &self.state.raw_swf;
+        if !matches!(raw_swf.get(..3), Some(b"FWS" | b"CWS" | b"ZWS")) {
+            return Err(ChartParseError::Decompression(
+                "Invalid swf signature.".to_string(),
+            ));
+        }
+        if let Some(length) = raw_swf.get(4..8) {
+            let length = u32::from_le_bytes([length[0], length[1], length[2], length[3]]);
+            if length > MAX_SWF_SIZE {
+                return Err(ChartParseError::TooLarge(length));
+            }
+        }
+
+        let stream = swf::decompress_swf(self.state.raw_swf.as_slice())
+            .map_err(|e| ChartParseError::Decompression(e.to_string()))?;
         Ok(SwfParser {
             state: ReadyToParse { stream },
         })
@@ -87,164 +168,245 @@ impl SwfParser<ReadyToParse> {
         SwfParser {
             state: Parsing {
                 stream: self.state.stream,
+                position: 0,
+                tags_read: 0,
                 mp3: Vec::new(),
+                audio: None,
+                metadata: Metadata::default(),
                 chart: Vec::new(),
             },
         }
     }
 }
 
-// TODO: Make this parse function async.
 impl SwfParser<Parsing> {
-    #[must_use]
-    pub fn tick(&mut self) -> ParsingState {
-        let mut swf_reader = Reader::new(
-            &self.state.stream.data[..],
-            self.state.stream.header.version(),
-        );
-        while let Ok(tag) = swf_reader.read_tag() {
+    /// Read the next [`DEFAULT_TICK_BUDGET`] bytes worth of tags.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ChartParseError`] if a tag is malformed.
+    pub fn tick(&mut self) -> Result<ParsingState, ChartParseError> {
+        self.tick_for(DEFAULT_TICK_BUDGET)
+    }
+
+    /// Read tags until at least `byte_budget` bytes have been consumed or the swf ends.
+    ///
+    /// At least one tag is read per call, so a single large tag can exceed the budget.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ChartParseError`] if a tag is malformed.
+    pub fn tick_for(&mut self, byte_budget: usize) -> Result<ParsingState, ChartParseError> {
+        let data = &self.state.stream.data[..];
+        let start = self.state.position.min(data.len());
+        let mut swf_reader = Reader::new(&data[start..], self.state.stream.header.version());
+
+        let mut finished = false;
+        while data.len() - swf_reader.get_ref().len() - start < byte_budget.max(1) {
+            if swf_reader.get_ref().is_empty() {
+                finished = true;
+                break;
+            }
+
+            let location = Location {
+                tag: self.state.tags_read,
+                byte: data.len() - swf_reader.get_ref().len(),
+            };
+            let tag = swf_reader.read_tag().map_err(|e| ChartParseError::Tag {
+                location,
+                message: e.to_string(),
+            })?;
+            self.state.tags_read = self.state.tags_read.saturating_add(1);
+
             match tag {
+                swf::Tag::End => {
+                    finished = true;
+                    break;
+                }
+
                 // This is for files that do not have a block of audio at the front.
                 swf::Tag::DefineSound(sound) => {
-                    //println!("DefineSound: {:?}", sound)
+                    self.state
+                        .audio
+                        .get_or_insert_with(|| audio_info(&sound.format, 0));
                     self.state.mp3.extend_from_slice(sound.data);
                 }
 
                 swf::Tag::DoAction(action) => {
-                    let res = SwfParser::parse_action(action, swf_reader.version());
-                    match res {
-                        Ok(chart) => self.state.chart = chart,
-                        Err(e) => println!("Error when parsing the swf: {}", e),
+                    match SwfParser::parse_action(action, swf_reader.version(), location) {
+                        Ok(Some(chart)) => self.state.chart = chart,
+                        Ok(None) => {}
+                        // Chart data may be in a later tag, so only this one is skipped.
+                        Err(e @ ChartParseError::UnexpectedAction { .. }) => {
+                            log::error!("Skipping tag: {e}");
+                        }
+                        Err(e) => return Err(e),
                     }
                 }
 
                 // One Shot of all of the audio
-                // TODO: Bug: If the length of the tag - 4 is 0, it is invalid.
                 swf::Tag::SoundStreamBlock(sound) => {
-                    if sound.len() - 4 == 0 {
-                        log::error!("No song data, this is invalid!")
+                    if sound.len() <= 4 {
+                        return Err(ChartParseError::TruncatedAudio { location });
                     }
                     self.state.mp3.extend_from_slice(sound);
                 }
 
                 // This is audio metadata.
-                swf::Tag::SoundStreamHead(ssh) => {
-                    log::info!("SoundStreamHead");
-                    log::info!("latency seek: {}", ssh.latency_seek);
-                    log::info!("playback format: {:?}", ssh.playback_format);
-                    log::info!("num samples per block: {}", ssh.num_samples_per_block);
-                    log::info!("stream format: {:?}", ssh.stream_format);
+                swf::Tag::SoundStreamHead(ssh) | swf::Tag::SoundStreamHead2(ssh) => {
+                    self.state.audio = Some(audio_info(&ssh.stream_format, ssh.latency_seek));
                 }
-                swf::Tag::SoundStreamHead2(ssh) => {
-                    log::info!("SoundStreamHead");
-                    log::info!("latency seek: {}", ssh.latency_seek);
-                    log::info!("playback format: {:?}", ssh.playback_format);
-                    log::info!("num samples per block: {}", ssh.num_samples_per_block);
-                    log::info!("stream format: {:?}", ssh.stream_format);
+
+                swf::Tag::Metadata(xmp) => {
+                    let xmp = xmp.to_str_lossy(UTF_8);
+                    if let Some(title) = xmp_field(&xmp, "dc:title") {
+                        self.state.metadata.title = title;
+                    }
+                    // Whoever made the swf, the artist only comes from FFR's playlist.
+                    if let Some(author) = xmp_field(&xmp, "dc:creator") {
+                        self.state.metadata.author = author;
+                    }
                 }
                 _ => {}
             }
         }
 
-        ParsingState::Finished
+        self.state.position = data.len() - swf_reader.get_ref().len();
+        if finished || self.state.position >= data.len() {
+            self.state.position = data.len();
+            Ok(ParsingState::Finished)
+        } else {
+            Ok(ParsingState::Parsing(
+                self.state.position as f32 / data.len() as f32 * 100.,
+            ))
+        }
     }
 
-    pub fn finish(self) -> SwfParser<Parsed> {
-        SwfParser {
+    /// # Errors
+    ///
+    /// Will return [`ChartParseError::MissingChart`] if no tag contained chart data.
+    pub fn finish(self) -> Result<SwfParser<Parsed>, ChartParseError> {
+        if self.state.chart.is_empty() {
+            return Err(ChartParseError::MissingChart);
+        }
+
+        Ok(SwfParser {
             state: Parsed {
                 mp3: self.state.mp3,
+                audio: self.state.audio,
+                metadata: self.state.metadata,
                 chart: RuntimeChart::new(&self.state.chart),
             },
-        }
+        })
     }
 
-    fn parse_action(action_raw: &[u8], version: u8) -> anyhow::Result<Vec<RuntimeNote>> {
+    /// Returns `Ok(None)` for actions that aren't chart data, like frame scripts.
+    fn parse_action(
+        action_raw: &[u8],
+        version: u8,
+        location: Location,
+    ) -> Result<Option<Vec<RuntimeNote>>, ChartParseError> {
         let mut action_reader = avm1::read::Reader::new(action_raw, version);
         let mut constant_pool: Option<ConstantPool<'_>> = None;
         let mut value_stack: Vec<Value<'_>> = Vec::with_capacity(6);
         let mut beat_box: Vec<RuntimeNote> = Vec::new();
 
-        let mut done = false;
-        while !done {
-            if let Ok(action) = action_reader.read_action() {
-                match action {
-                    avm1::types::Action::ConstantPool(cp) => {
-                        let is_chart_data = cp.strings.contains(&SwfStr::from_utf8_str("beatBox"));
-                        if is_chart_data {
-                            constant_pool.replace(cp);
-                        } else {
-                            break;
-                        }
+        loop {
+            let action = match action_reader.read_action() {
+                Ok(action) => action,
+                Err(_) if constant_pool.is_none() => return Ok(None),
+                Err(e) => {
+                    return Err(ChartParseError::Action {
+                        location,
+                        message: e.to_string(),
+                    })
+                }
+            };
+
+            match action {
+                avm1::types::Action::ConstantPool(cp) => {
+                    let is_chart_data = cp.strings.contains(&SwfStr::from_utf8_str("beatBox"));
+                    if is_chart_data {
+                        constant_pool.replace(cp);
+                    } else {
+                        break;
                     }
+                }
 
-                    avm1::types::Action::Push(mut push_object) => {
-                        if let ControlFlow::Break(_) =
-                            parse_push_action(&mut push_object, &mut value_stack)
-                        {
-                            continue;
-                        }
+                avm1::types::Action::Push(mut push_object) => {
+                    if let ControlFlow::Break(_) =
+                        parse_push_action(&mut push_object, &mut value_stack)
+                    {
+                        continue;
                     }
+                }
 
-                    avm1::types::Action::End => {
-                        done = true;
-                    }
+                avm1::types::Action::End => {
+                    break;
+                }
 
-                    avm1::types::Action::GetVariable => {
-                        // Pop the last item on the stack and do something with it.
+                avm1::types::Action::GetVariable => {
+                    // Pop the last item on the stack and do something with it.
+                }
+
+                avm1::types::Action::InitArray => {
+                    // Ignore the first `InitArray`, data at this point is garbage.
+                    if value_stack.is_empty() || constant_pool.is_none() {
+                        continue;
                     }
 
-                    avm1::types::Action::InitArray => {
-                        // Ignore the first `InitArray`, data at this point is garbage.
-                        if value_stack.is_empty() {
-                            continue;
-                        }
+                    let items_to_pop = match value_stack.pop() {
+                        Some(Value::Int(length)) => usize::try_from(length)
+                            .map_err(|_| ChartParseError::NoteLength { location })?,
+                        _ => return Err(ChartParseError::NoteLength { location }),
+                    };
 
-                        let items_to_pop = if let Some(Value::Int(length)) = value_stack.pop() {
-                            length
-                        } else {
-                            anyhow::bail!("There was not an int on the end so you broke it.")
-                        };
+                    if value_stack.len() < items_to_pop {
+                        // We're done!
+                        break; // Probably
+                    }
 
-                        if value_stack.len() < items_to_pop as usize {
-                            // We're done!
-                            break; // Probably
-                        }
+                    let beat_position = parse_beat_position(&mut value_stack, location)?;
+                    let direction = parse_direction(&mut value_stack, &constant_pool, location)?;
+
+                    let color = match items_to_pop > 2 {
+                        true => parse_color(&mut value_stack, &constant_pool, location)?,
+                        false => NoteColor::Blue,
+                    };
+
+                    let timestamp = match items_to_pop > 3 {
+                        true => parse_timestamp(&mut value_stack, location)?,
+                        false => ((beat_position as f64 / 30.) * 1000.) as u32,
+                    };
+
+                    beat_box.push(RuntimeNote {
+                        beat_position,
+                        lane: direction.into(),
+                        color,
+                        timestamp,
+                        kind: NoteKind::Tap,
+                    });
+                }
 
-                        let beat_position = parse_beat_position(&mut value_stack)?;
-                        let direction = parse_direction(&mut value_stack, &constant_pool)?;
-
-                        let color = match items_to_pop > 2 {
-                            true => parse_color(&mut value_stack, &constant_pool)?,
-                            false => NoteColor::Blue,
-                        };
-
-                        let timestamp = match items_to_pop > 3 {
-                            true => parse_timestamp(&mut value_stack)?,
-                            false => ((beat_position as f64 / 30.) * 1000.) as u32,
-                        };
-
-                        beat_box.push(RuntimeNote {
-                            beat_position,
-                            direction,
-                            color,
-                            timestamp,
-                        });
-                    }
+                avm1::types::Action::SetMember | avm1::types::Action::SetVariable => {}
 
-                    avm1::types::Action::SetMember => {}
+                // Frame scripts that aren't chart data can do whatever they like.
+                _ if constant_pool.is_none() => {}
 
-                    _ => {
-                        log::error!("Unexpectedly unhandled action: {:?}", action);
-                    }
+                _ => {
+                    return Err(ChartParseError::UnexpectedAction {
+                        location,
+                        action: format!("{action:?}"),
+                    });
                 }
             }
         }
 
         if beat_box.is_empty() {
-            anyhow::bail!("Not chart data.");
+            return Ok(None);
         }
 
-        Ok(beat_box)
+        Ok(Some(beat_box))
     }
 }
 
@@ -255,24 +417,24 @@ impl SwfParser<Parsed> {
     }
 }
 
-fn parse_timestamp(value_stack: &mut Vec<Value<'_>>) -> anyhow::Result<u32> {
+fn parse_timestamp(
+    value_stack: &mut Vec<Value<'_>>,
+    location: Location,
+) -> Result<u32, ChartParseError> {
     if let Some(Value::Int(ms)) = value_stack.pop() {
         Ok(ms.unsigned_abs())
     } else {
-        anyhow::bail!(ChartParseError::Timestamp);
+        Err(ChartParseError::Timestamp { location })
     }
 }
 
 fn parse_color(
     value_stack: &mut Vec<Value<'_>>,
     constant_pool: &Option<ConstantPool<'_>>,
-) -> anyhow::Result<NoteColor> {
+    location: Location,
+) -> Result<NoteColor, ChartParseError> {
     if let Some(Value::ConstantPool(color)) = value_stack.pop() {
-        match constant_pool.clone().unwrap().strings[color as usize]
-            .to_str_lossy(UTF_8)
-            .to_string()
-            .as_str()
-        {
+        match constant_pool_str(constant_pool, color, location)?.as_str() {
             "red" => Ok(NoteColor::Red),
             "yellow" => Ok(NoteColor::Yellow),
             "blue" => Ok(NoteColor::Blue),
@@ -282,43 +444,93 @@ fn parse_color(
             "purple" => Ok(NoteColor::Purple),
             "cyan" => Ok(NoteColor::Cyan),
             "white" => Ok(NoteColor::White),
-            _ => anyhow::bail!(ChartParseError::NoteColor),
+            _ => Err(ChartParseError::NoteColor { location }),
         }
     } else {
-        anyhow::bail!(ChartParseError::NoteColor);
+        Err(ChartParseError::NoteColor { location })
     }
 }
 
 fn parse_direction(
     value_stack: &mut Vec<Value<'_>>,
     constant_pool: &Option<ConstantPool<'_>>,
-) -> anyhow::Result<Direction> {
+    location: Location,
+) -> Result<Direction, ChartParseError> {
     if let Some(Value::ConstantPool(dir)) = value_stack.pop() {
-        match constant_pool.clone().unwrap().strings[dir as usize]
-            .to_str_lossy(UTF_8)
-            .to_string()
-            .as_str()
-        {
+        match constant_pool_str(constant_pool, dir, location)?.as_str() {
             "L" => Ok(Direction::Left),
             "U" => Ok(Direction::Up),
             "D" => Ok(Direction::Down),
             "R" => Ok(Direction::Right),
-            _ => anyhow::bail!(ChartParseError::NoteDirection),
+            _ => Err(ChartParseError::NoteDirection { location }),
         }
     } else {
-        anyhow::bail!(ChartParseError::NoteDirection);
+        Err(ChartParseError::NoteDirection { location })
     }
 }
 
-fn parse_beat_position(value_stack: &mut Vec<Value<'_>>) -> anyhow::Result<u32> {
+fn parse_beat_position(
+    value_stack: &mut Vec<Value<'_>>,
+    location: Location,
+) -> Result<u32, ChartParseError> {
     if let Some(Value::Int(ms)) = value_stack.pop() {
         Ok(ms.unsigned_abs())
     } else {
-        log::error!("No beat position found");
-        anyhow::bail!(ChartParseError::BeatPosition);
+        Err(ChartParseError::BeatPosition { location })
     }
 }
 
+fn constant_pool_str(
+    constant_pool: &Option<ConstantPool<'_>>,
+    index: u16,
+    location: Location,
+) -> Result<String, ChartParseError> {
+    constant_pool
+        .as_ref()
+        .and_then(|pool| pool.strings.get(usize::from(index)))
+        .map(|value| value.to_str_lossy(UTF_8).to_string())
+        .ok_or(ChartParseError::ConstantPool { location, index })
+}
+
+fn audio_info(format: &swf::SoundFormat, latency_seek: i16) -> AudioInfo {
+    AudioInfo {
+        format: match format.compression {
+            swf::AudioCompression::Mp3 => AudioFormat::Mp3,
+            swf::AudioCompression::Adpcm => AudioFormat::Adpcm,
+            swf::AudioCompression::Uncompressed => AudioFormat::Pcm,
+            swf::AudioCompression::UncompressedUnknownEndian => AudioFormat::PcmNativeEndian,
+            swf::AudioCompression::Nellymoser
+            | swf::AudioCompression::Nellymoser8Khz
+            | swf::AudioCompression::Nellymoser16Khz => AudioFormat::Nellymoser,
+            swf::AudioCompression::Aac => AudioFormat::Aac,
+            swf::AudioCompression::Speex => AudioFormat::Speex,
+        },
+        sample_rate: u32::from(format.sample_rate),
+        is_stereo: format.is_stereo,
+        is_16_bit: format.is_16_bit,
+        latency_seek,
+    }
+}
+
+/// Pull the text of the first `field` element out of an XMP packet.
+///
+/// Values wrapped in `rdf:Alt`/`rdf:Seq` lists yield their first `rdf:li`.
+fn xmp_field(xmp: &str, field: &str) -> Option<String> {
+    let element = &xmp[xmp.find(&format!("<{field}"))?..];
+    let body = element.get(element.find('>')? + 1..element.find(&format!("</{field}>"))?)?;
+
+    let text = match body.find("<rdf:li") {
+        Some(start) => {
+            let item = &body[start..];
+            item.get(item.find('>')? + 1..item.find("</rdf:li>")?)?
+        }
+        None => body,
+    };
+
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
 fn parse_push_action<'a>(
     pushed_objects: &mut avm1::types::Push<'a>,
     value_stack: &mut Vec<Value<'a>>,
@@ -336,9 +548,8 @@ mod tests {
     use crate::NoteColor;
 
     use super::SwfParser;
-    use anyhow::{self, Result};
 
-    fn parse_chart(raw_swf: &[u8]) -> Result<Parsed, swf::error::Error> {
+    fn parse_chart(raw_swf: &[u8]) -> Result<Parsed, ChartParseError> {
         let mut vec = Vec::<u8>::new();
         vec.extend_from_slice(raw_swf);
 
@@ -347,15 +558,15 @@ mod tests {
 
         let mut parsing = parser_decomp_result.parse();
 
-        let _state = parsing.tick();
-        let parsed = parsing.finish();
+        while let ParsingState::Parsing(_) = parsing.tick()? {}
+        let parsed = parsing.finish()?;
 
         let chart = parsed.consume();
         Ok(chart)
     }
 
     #[test]
-    pub fn test_parse_2_cell_chart() -> Result<(), swf::error::Error> {
+    pub fn test_parse_2_cell_chart() -> Result<(), ChartParseError> {
         let swf = include_bytes!("./test_assets/test_2.swf");
         let chart = parse_chart(swf)?;
         assert!(chart.chart.notes[0].color == NoteColor::Blue);
@@ -363,7 +574,7 @@ mod tests {
     }
 
     #[test]
-    pub fn test_parse_3_cell_chart() -> Result<(), swf::error::Error> {
+    pub fn test_parse_3_cell_chart() -> Result<(), ChartParseError> {
         let swf = include_bytes!("./test_assets/test_3.swf");
         let chart = parse_chart(swf)?;
         assert!(chart.chart.notes[0].color != NoteColor::Blue);
@@ -371,7 +582,7 @@ mod tests {
     }
 
     #[test]
-    pub fn test_parse_4_cell_chart() -> Result<(), swf::error::Error> {
+    pub fn test_parse_4_cell_chart() -> Result<(), ChartParseError> {
         let swf = include_bytes!("./test_assets/test_4.swf");
         let chart = parse_chart(swf)?;
         assert!(chart.chart.notes[0].color != NoteColor::Blue);
@@ -379,10 +590,218 @@ mod tests {
     }
 
     #[test]
-    pub fn test_parse_block_audio() -> Result<(), swf::error::Error> {
+    pub fn test_parse_in_small_ticks() -> Result<(), ChartParseError> {
+        let swf = include_bytes!("./test_assets/test_4.swf");
+        let mut parsing = SwfParser::new(swf.to_vec()).decompress()?.parse();
+
+        let mut ticks = 0;
+        let mut last_progress = 0.;
+        while let ParsingState::Parsing(progress) = parsing.tick_for(1)? {
+            assert!(progress >= last_progress);
+            last_progress = progress;
+            ticks += 1;
+        }
+        assert!(ticks > 0);
+
+        let chart = parsing.finish()?.consume();
+        let expected = parse_chart(swf)?;
+        assert_eq!(chart.chart, expected.chart);
+        assert_eq!(chart.mp3, expected.mp3);
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_parse_block_audio() -> Result<(), ChartParseError> {
         let swf = include_bytes!("./test_assets/test_4_block.swf");
         let chart = parse_chart(swf)?;
         assert!(chart.mp3.len() > 0);
         Ok(())
     }
+
+    #[test]
+    pub fn test_reject_garbage() {
+        let result = SwfParser::new(b"not a swf".to_vec()).decompress();
+        assert!(matches!(result, Err(ChartParseError::Decompression(_))));
+    }
+
+    #[test]
+    pub fn test_reject_truncated_swf() -> Result<(), ChartParseError> {
+        let swf = include_bytes!("./test_assets/test_4.swf");
+        let mut stream = swf::decompress_swf(&swf[..])
+            .map_err(|e| ChartParseError::Decompression(e.to_string()))?;
+
+        // Cut the body off in the middle of a tag.
+        stream.data.truncate(stream.data.len() / 2);
+        let result = SwfParser {
+            state: ReadyToParse { stream },
+        }
+        .parse()
+        .tick_for(usize::MAX);
+
+        assert!(matches!(
+            result,
+            Err(ChartParseError::Tag { .. } | ChartParseError::TruncatedAudio { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_constant_pool_out_of_bounds() {
+        let pool = None;
+        let result = constant_pool_str(&pool, 3, Location::default());
+        assert!(matches!(
+            result,
+            Err(ChartParseError::ConstantPool { index: 3, .. })
+        ));
+    }
+
+    /// Wrap `tags` in an uncompressed swf, each tag is `(code, body)`.
+    fn swf_with_tags(tags: &[(u16, &[u8])]) -> Vec<u8> {
+        // Empty stage rect, 30fps, 1 frame.
+        let mut body = vec![0x00, 0x00, 0x1e, 0x01, 0x00];
+        for (code, tag) in tags {
+            body.extend_from_slice(&((code << 6) | 0x3f).to_le_bytes());
+            body.extend_from_slice(&(tag.len() as u32).to_le_bytes());
+            body.extend_from_slice(tag);
+        }
+        body.extend_from_slice(&[0x00, 0x00]);
+
+        let mut swf = b"FWS\x0a".to_vec();
+        swf.extend_from_slice(&(body.len() as u32 + 8).to_le_bytes());
+        swf.extend_from_slice(&body);
+        swf
+    }
+
+    const DO_ACTION: u16 = 12;
+    const SOUND_STREAM_BLOCK: u16 = 19;
+    const SOUND_STREAM_HEAD: u16 = 18;
+    const METADATA: u16 = 77;
+
+    /// `ConstantPool ["beatBox", "L"]` followed by `actions`.
+    fn chart_action(actions: &[u8]) -> Vec<u8> {
+        let mut action = vec![0x88, 0x0c, 0x00, 0x02, 0x00];
+        action.extend_from_slice(b"beatBox\0L\0");
+        action.extend_from_slice(actions);
+        action
+    }
+
+    #[test]
+    pub fn test_reject_huge_declared_length() {
+        let result = SwfParser::new(b"FWS\x0a\xff\xff\xff\xff".to_vec()).decompress();
+        assert!(matches!(result, Err(ChartParseError::TooLarge(u32::MAX))));
+    }
+
+    #[test]
+    pub fn test_reject_empty_sound_block() -> Result<(), ChartParseError> {
+        let swf = swf_with_tags(&[(SOUND_STREAM_BLOCK, &[])]);
+        let result = SwfParser::new(swf).decompress()?.parse().tick();
+        assert!(matches!(
+            result,
+            Err(ChartParseError::TruncatedAudio { location }) if location.tag == 0
+        ));
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_reject_constant_pool_index() -> Result<(), ChartParseError> {
+        // Push [pool 9, 30, 2], InitArray, End
+        let action = chart_action(&[
+            0x96, 0x0c, 0x00, 0x08, 0x09, 0x07, 0x1e, 0x00, 0x00, 0x00, 0x07, 0x02, 0x00, 0x00,
+            0x00, 0x42, 0x00,
+        ]);
+        let swf = swf_with_tags(&[(DO_ACTION, &action)]);
+        let result = SwfParser::new(swf).decompress()?.parse().tick();
+        assert!(matches!(
+            result,
+            Err(ChartParseError::ConstantPool { index: 9, .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_reject_negative_array_length() -> Result<(), ChartParseError> {
+        // Push [pool 1, 30, -1], InitArray, End
+        let action = chart_action(&[
+            0x96, 0x0c, 0x00, 0x08, 0x01, 0x07, 0x1e, 0x00, 0x00, 0x00, 0x07, 0xff, 0xff, 0xff,
+            0xff, 0x42, 0x00,
+        ]);
+        let swf = swf_with_tags(&[(DO_ACTION, &action)]);
+        let result = SwfParser::new(swf).decompress()?.parse().tick();
+        assert!(matches!(result, Err(ChartParseError::NoteLength { .. })));
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_reject_truncated_action() -> Result<(), ChartParseError> {
+        // A push that claims more bytes than the tag holds used to spin forever.
+        let action = chart_action(&[0x96, 0x20, 0x00, 0x07]);
+        let swf = swf_with_tags(&[(DO_ACTION, &action)]);
+        let result = SwfParser::new(swf).decompress()?.parse().tick();
+        assert!(matches!(result, Err(ChartParseError::Action { .. })));
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_skip_unexpected_action() -> Result<(), ChartParseError> {
+        // Stop, End
+        let action = chart_action(&[0x07, 0x00]);
+        let location = Location { tag: 1, byte: 5 };
+        assert!(matches!(
+            SwfParser::parse_action(&action, 10, location),
+            Err(ChartParseError::UnexpectedAction { location: l, .. }) if l == location
+        ));
+
+        let swf = swf_with_tags(&[(DO_ACTION, &action)]);
+        let result = SwfParser::new(swf).decompress()?.parse().tick();
+        assert!(matches!(result, Ok(ParsingState::Finished)));
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_ignore_frame_scripts() -> Result<(), ChartParseError> {
+        // Stop, End
+        let swf = swf_with_tags(&[(DO_ACTION, &[0x07, 0x00])]);
+        let mut parsing = SwfParser::new(swf).decompress()?.parse();
+        assert!(matches!(parsing.tick()?, ParsingState::Finished));
+        assert!(matches!(
+            parsing.finish(),
+            Err(ChartParseError::MissingChart)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_read_audio_and_metadata() -> Result<(), ChartParseError> {
+        // Mp3, 44kHz, 16 bit, stereo for both formats, 1152 samples per block, 1105 latency.
+        let head = [0x0f, 0x2f, 0x80, 0x04, 0x51, 0x04];
+        let xmp = b"<x:xmpmeta><rdf:RDF><rdf:Description>\
+            <dc:title><rdf:Alt><rdf:li xml:lang=\"x-default\">Song</rdf:li></rdf:Alt></dc:title>\
+            <dc:creator><rdf:Seq><rdf:li>Stepper</rdf:li></rdf:Seq></dc:creator>\
+            </rdf:Description></rdf:RDF></x:xmpmeta>\0";
+        let swf = swf_with_tags(&[(SOUND_STREAM_HEAD, &head), (METADATA, xmp)]);
+
+        let mut parsing = SwfParser::new(swf).decompress()?.parse();
+        while let ParsingState::Parsing(_) = parsing.tick()? {}
+
+        let audio = parsing.state.audio.expect("stream head was read");
+        assert_eq!(audio.format, AudioFormat::Mp3);
+        assert_eq!(audio.sample_rate, 44100);
+        assert!(audio.is_stereo);
+        assert_eq!(audio.latency_seek, 1105);
+        assert_eq!(parsing.state.metadata.title, "Song");
+        assert_eq!(parsing.state.metadata.author, "Stepper");
+        assert_eq!(parsing.state.metadata.artist, "");
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_xmp_field() {
+        assert_eq!(
+            xmp_field("<dc:title>Plain</dc:title>", "dc:title").as_deref(),
+            Some("Plain")
+        );
+        assert_eq!(xmp_field("<dc:title></dc:title>", "dc:title"), None);
+        assert_eq!(xmp_field("<dc:title>", "dc:title"), None);
+        assert_eq!(xmp_field("<dc:title</dc:title>", "dc:title"), None);
+    }
 }
@@ -0,0 +1,107 @@
+//! Colors notes by where they fall in the beat, the way FFR colors its own charts.
+//!
+//! Only swf charts come with colors, every other importer derives them here from its timing
+//! points.
+
+use crate::{NoteColor, RuntimeChart, TimingPoint};
+
+/// How far off a grid line (in milliseconds) a note may be and still count as on it.
+/// Formats that store whole milliseconds round every note by up to half of one.
+const TOLERANCE_MS: f64 = 2.;
+
+/// Notes per beat and the color of notes first landing on that grid, 4ths being one per beat.
+const QUANTIZATIONS: [(u32, NoteColor); 8] = [
+    (1, NoteColor::Red),
+    (2, NoteColor::Blue),
+    (3, NoteColor::Purple),
+    (4, NoteColor::Yellow),
+    (6, NoteColor::Pink),
+    (8, NoteColor::Orange),
+    (12, NoteColor::Cyan),
+    (16, NoteColor::Green),
+];
+
+/// The color of a note at `timestamp`, measured from the timing point in effect then.
+///
+/// Returns [`None`] without timing points to measure from.
+#[must_use]
+pub fn color_at(timing: &[TimingPoint], timestamp: u32) -> Option<NoteColor> {
+    let ms = f64::from(timestamp);
+    let tempos = timing
+        .iter()
+        .filter(|timing_point| timing_point.micros_per_beat > 0);
+    // Notes before the first timing point are measured backwards from it.
+    let timing_point = tempos
+        .clone()
+        .take_while(|timing_point| f64::from(timing_point.timestamp) <= ms)
+        .last()
+        .or_else(|| tempos.clone().next())?;
+
+    let ms_per_beat = timing_point.ms_per_beat();
+    let beats = (ms - f64::from(timing_point.timestamp)) / ms_per_beat;
+
+    let color = QUANTIZATIONS
+        .into_iter()
+        .find(|&(per_beat, _)| {
+            let steps = beats * f64::from(per_beat);
+            let off_grid_ms = (steps - steps.round()).abs() * ms_per_beat / f64::from(per_beat);
+            off_grid_ms <= TOLERANCE_MS
+        })
+        .map_or(NoteColor::White, |(_, color)| color);
+
+    Some(color)
+}
+
+impl RuntimeChart {
+    /// Recolor every note from its position in the beat, see [`color_at`].
+    ///
+    /// Charts without timing points are returned unchanged.
+    #[must_use]
+    pub fn with_quantized_colors(mut self) -> Self {
+        for note in &mut self.notes {
+            if let Some(color) = color_at(&self.timing, note.timestamp) {
+                note.color = color;
+            }
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_colors_by_beat() {
+        // 120 BPM, a beat every 500ms.
+        let timing = [TimingPoint::new(100, 500_000)];
+        let color = |offset: f64| color_at(&timing, (100. + offset).round() as u32);
+
+        assert_eq!(color(1000.), Some(NoteColor::Red));
+        assert_eq!(color(250.), Some(NoteColor::Blue));
+        assert_eq!(color(500. / 3.), Some(NoteColor::Purple));
+        assert_eq!(color(125.), Some(NoteColor::Yellow));
+        assert_eq!(color(500. / 6.), Some(NoteColor::Pink));
+        assert_eq!(color(62.5), Some(NoteColor::Orange));
+        assert_eq!(color(500. / 12.), Some(NoteColor::Cyan));
+        assert_eq!(color(31.25), Some(NoteColor::Green));
+        assert_eq!(color(10.), Some(NoteColor::White));
+    }
+
+    #[test]
+    pub fn test_follows_tempo_changes() {
+        let timing = [
+            TimingPoint::new(0, 500_000),
+            TimingPoint::new(1100, 300_000),
+        ];
+
+        assert_eq!(color_at(&timing, 1250), Some(NoteColor::Blue));
+        assert_eq!(color_at(&timing, 1400), Some(NoteColor::Red));
+        // Before the first timing point.
+        assert_eq!(
+            color_at(&[TimingPoint::new(1000, 500_000)], 250),
+            Some(NoteColor::Blue)
+        );
+        assert_eq!(color_at(&[], 250), None);
+    }
+}
@@ -0,0 +1,2 @@
+#[cfg(feature = "sm")]
+pub mod sm;
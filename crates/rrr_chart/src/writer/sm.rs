@@ -0,0 +1,440 @@
+//! Writes charts as StepMania simfiles (`.sm` and `.ssc`).
+//!
+//! Notes are quantized to the nearest 192nd of a measure. Charts with [timing points](crate::TimingPoint)
+//! keep their tempo changes, charts without them get a single BPM derived from the spacing of
+//! their notes.
+//!
+//! Notes are placed by their `beat_position` when their `timestamp` was derived from it, the
+//! frame is exact where the timestamp was truncated to whole milliseconds.
+
+use crate::{Metadata, NoteKind, RuntimeChart, RuntimeNote};
+use rrr_types::LaneCount;
+use std::{collections::BTreeMap, fmt::Write};
+
+const BEATS_PER_MEASURE: u32 = 4;
+const ROWS_PER_MEASURE: u32 = 192;
+const ROWS_PER_BEAT: u32 = ROWS_PER_MEASURE / BEATS_PER_MEASURE;
+const LINES_PER_MEASURE: [u32; 10] = [4, 8, 12, 16, 24, 32, 48, 64, 96, 192];
+const DERIVED_BPM_RANGE: (f64, f64) = (100., 200.);
+const FALLBACK_BPM: f64 = 120.;
+/// FFR positions notes on a 30 frames per second grid.
+const FRAMES_PER_SECOND: u32 = 30;
+
+/// The tempo of a chart, as written to `#OFFSET` and `#BPMS`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timing {
+    /// Milliseconds into the audio where beat 0 lands.
+    pub offset: f64,
+    /// Beat and BPM pairs, the first is always at beat 0.
+    pub bpms: Vec<(f64, f64)>,
+    segments: Vec<Segment>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Segment {
+    ms: f64,
+    beat: f64,
+    ms_per_beat: f64,
+}
+
+impl Timing {
+    #[must_use]
+    pub fn from_chart(chart: &RuntimeChart) -> Self {
+        let first_note = chart
+            .notes
+            .iter()
+            .map(note_ms)
+            .min_by(f64::total_cmp)
+            .unwrap_or(0.);
+
+        let tempos: Vec<(f64, f64)> = if chart.timing.is_empty() {
+            vec![(first_note, derive_bpm(chart))]
+        } else {
+            chart
+                .timing
+                .iter()
+                .filter(|timing_point| timing_point.micros_per_beat > 0)
+                .map(|timing_point| {
+                    (
+                        f64::from(timing_point.timestamp),
+                        round_3(timing_point.bpm()),
+                    )
+                })
+                .collect()
+        };
+
+        let Some(&(first_ms, first_bpm)) = tempos.first() else {
+            return Self::from_chart(&RuntimeChart::new(&chart.notes));
+        };
+
+        // Move beat 0 back by whole beats until it lands at or before the first note.
+        let first_ms_per_beat = 60_000. / first_bpm;
+        let beats_before = ((first_ms - first_note) / first_ms_per_beat).ceil();
+        let mut offset = first_ms - beats_before.max(0.) * first_ms_per_beat;
+        if chart.timing.is_empty() {
+            offset %= first_ms_per_beat;
+        }
+        let offset = offset.round();
+
+        let mut segments = vec![Segment {
+            ms: offset,
+            beat: 0.,
+            ms_per_beat: first_ms_per_beat,
+        }];
+        let mut bpms = vec![(0., first_bpm)];
+
+        for &(ms, bpm) in tempos.iter().skip(1) {
+            let previous = &segments[segments.len() - 1];
+            let beat = round_3(previous.beat + (ms - previous.ms) / previous.ms_per_beat);
+            bpms.push((beat, bpm));
+            segments.push(Segment {
+                ms,
+                beat,
+                ms_per_beat: 60_000. / bpm,
+            });
+        }
+
+        Self {
+            offset,
+            bpms,
+            segments,
+        }
+    }
+
+    /// Converts a timestamp in milliseconds to a beat.
+    #[must_use]
+    pub fn beat_at(&self, ms: u32) -> f64 {
+        self.beat_at_ms(f64::from(ms))
+    }
+
+    fn beat_at_ms(&self, ms: f64) -> f64 {
+        let segment = self
+            .segments
+            .iter()
+            .rev()
+            .find(|segment| segment.ms <= ms)
+            .unwrap_or(&self.segments[0]);
+        segment.beat + (ms - segment.ms) / segment.ms_per_beat
+    }
+}
+
+/// Writes a `.sm` simfile with a single chart, see [`steps_type`] for how lanes are mapped.
+#[must_use]
+pub fn write_sm(chart: &RuntimeChart, metadata: &Metadata, music: &str) -> String {
+    let timing = Timing::from_chart(chart);
+    let mut out = String::new();
+
+    write_header(&mut out, &timing, metadata, music);
+    out.push_str("#NOTES:\n");
+    let _ = writeln!(out, "     {}:", steps_type(chart.lane_count));
+    let _ = writeln!(out, "     {}:", escape(&metadata.author));
+    out.push_str("     Challenge:\n");
+    out.push_str("     1:\n");
+    out.push_str("     0,0,0,0,0:\n");
+    write_notes(&mut out, chart, &timing);
+
+    out
+}
+
+/// Writes a `.ssc` simfile with a single chart, see [`steps_type`] for how lanes are mapped.
+#[must_use]
+pub fn write_ssc(chart: &RuntimeChart, metadata: &Metadata, music: &str) -> String {
+    let timing = Timing::from_chart(chart);
+    let steps_type = steps_type(chart.lane_count);
+    let mut out = String::new();
+
+    out.push_str("#VERSION:0.83;\n");
+    write_header(&mut out, &timing, metadata, music);
+    out.push('\n');
+    let _ = writeln!(out, "//---------------{steps_type} - ----------------");
+    out.push_str("#NOTEDATA:;\n");
+    let _ = writeln!(out, "#STEPSTYPE:{steps_type};");
+    out.push_str("#DESCRIPTION:;\n");
+    out.push_str("#DIFFICULTY:Challenge;\n");
+    out.push_str("#METER:1;\n");
+    out.push_str("#RADARVALUES:0,0,0,0,0;\n");
+    let _ = writeln!(out, "#CREDIT:{};", escape(&metadata.author));
+    out.push_str("#NOTES:\n");
+    write_notes(&mut out, chart, &timing);
+
+    out
+}
+
+/// The StepMania game mode with as many columns as `lanes`.
+#[must_use]
+pub fn steps_type(lanes: LaneCount) -> &'static str {
+    match lanes.get() {
+        5 => "pump-single",
+        6 => "dance-solo",
+        7 => "kb7-single",
+        8 => "dance-double",
+        _ => "dance-single",
+    }
+}
+
+fn write_header(out: &mut String, timing: &Timing, metadata: &Metadata, music: &str) {
+    let bpms = timing
+        .bpms
+        .iter()
+        .map(|(beat, bpm)| format!("{beat:.3}={bpm:.3}"))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let _ = writeln!(out, "#TITLE:{};", escape(&metadata.title));
+    let _ = writeln!(out, "#ARTIST:{};", escape(&metadata.artist));
+    let _ = writeln!(out, "#CREDIT:{};", escape(&metadata.author));
+    let _ = writeln!(out, "#MUSIC:{};", escape(music));
+    let _ = writeln!(out, "#OFFSET:{:.3};", -timing.offset / 1000.);
+    let _ = writeln!(out, "#BPMS:{bpms};");
+    out.push_str("#STOPS:;\n");
+}
+
+fn write_notes(out: &mut String, chart: &RuntimeChart, timing: &Timing) {
+    // Row (in 192nds of a measure) to what is on each lane of it.
+    let lanes = usize::from(chart.lane_count.get());
+    let mut rows: BTreeMap<u32, Vec<char>> = BTreeMap::new();
+    for note in &chart.notes {
+        let beat = timing.beat_at_ms(note_ms(note)).max(0.);
+        let row = (beat * f64::from(ROWS_PER_BEAT)).round() as u32;
+        if let Some(pressed) = rows
+            .entry(row)
+            .or_insert_with(|| vec!['0'; lanes])
+            .get_mut(usize::from(note.lane.0))
+        {
+            *pressed = match note.kind {
+                NoteKind::Tap => '1',
+                NoteKind::Mine => 'M',
+                NoteKind::Fake => 'F',
+            };
+        }
+    }
+
+    let measures = rows
+        .keys()
+        .next_back()
+        .map_or(1, |last_row| last_row / ROWS_PER_MEASURE + 1);
+
+    for measure in 0..measures {
+        let start = measure * ROWS_PER_MEASURE;
+        let measure_rows: Vec<(u32, &Vec<char>)> = rows
+            .range(start..start + ROWS_PER_MEASURE)
+            .map(|(row, pressed)| (row - start, pressed))
+            .collect();
+
+        let lines = LINES_PER_MEASURE
+            .into_iter()
+            .find(|lines| {
+                let step = ROWS_PER_MEASURE / lines;
+                measure_rows.iter().all(|(row, _)| row % step == 0)
+            })
+            .unwrap_or(ROWS_PER_MEASURE);
+        let step = ROWS_PER_MEASURE / lines;
+
+        if measure > 0 {
+            out.push_str(",\n");
+        }
+
+        for line in 0..lines {
+            match measure_rows.iter().find(|(row, _)| *row == line * step) {
+                Some((_, pressed)) => out.extend(pressed.iter()),
+                None => out.push_str(&"0".repeat(lanes)),
+            }
+            out.push('\n');
+        }
+    }
+
+    out.push_str(";\n");
+}
+
+/// When `note` happens in milliseconds, see the module docs.
+fn note_ms(note: &RuntimeNote) -> f64 {
+    let frame_ms = u64::from(note.beat_position) * 1000 / u64::from(FRAMES_PER_SECOND);
+    // Allow for the float rounding of the swf parser.
+    if frame_ms.abs_diff(u64::from(note.timestamp)) <= 1 {
+        f64::from(note.beat_position) * 1000. / f64::from(FRAMES_PER_SECOND)
+    } else {
+        f64::from(note.timestamp)
+    }
+}
+
+/// Picks the most common gap between note rows and folds it into a comfortable BPM range.
+fn derive_bpm(chart: &RuntimeChart) -> f64 {
+    // In microseconds, so gaps between frames compare equal.
+    let mut timestamps: Vec<u32> = chart
+        .notes
+        .iter()
+        .map(|note| (note_ms(note) * 1000.).round() as u32)
+        .collect();
+    timestamps.sort_unstable();
+    timestamps.dedup();
+
+    let mut gaps: BTreeMap<u32, usize> = BTreeMap::new();
+    for pair in timestamps.windows(2) {
+        *gaps.entry(pair[1] - pair[0]).or_default() += 1;
+    }
+
+    let Some((&gap, _)) = gaps
+        .iter()
+        .max_by(|(gap_a, count_a), (gap_b, count_b)| count_a.cmp(count_b).then(gap_b.cmp(gap_a)))
+    else {
+        return FALLBACK_BPM;
+    };
+
+    let mut bpm = 60_000_000. / f64::from(gap);
+    while bpm >= DERIVED_BPM_RANGE.1 {
+        bpm /= 2.;
+    }
+    while bpm < DERIVED_BPM_RANGE.0 {
+        bpm *= 2.;
+    }
+
+    round_3(bpm)
+}
+
+fn round_3(value: f64) -> f64 {
+    (value * 1000.).round() / 1000.
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(':', "\\:")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NoteColor, RuntimeNote, TimingPoint};
+    use rrr_types::{Direction, Lane};
+
+    fn note(timestamp: u32, direction: Direction) -> RuntimeNote {
+        RuntimeNote {
+            beat_position: timestamp * 30 / 1000,
+            color: NoteColor::Red,
+            lane: direction.into(),
+            timestamp,
+            kind: NoteKind::Tap,
+        }
+    }
+
+    fn metadata() -> Metadata {
+        Metadata {
+            title: "Title".to_string(),
+            artist: "Artist".to_string(),
+            author: "Author".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_write_sm_with_timing() {
+        let chart = RuntimeChart::new(&[
+            note(1000, Direction::Left),
+            note(1500, Direction::Down),
+            note(2000, Direction::Up),
+            note(2250, Direction::Right),
+        ])
+        .with_timing(&[TimingPoint::new(1000, 500_000)]);
+
+        let sm = write_sm(&chart, &metadata(), "song.mp3");
+        assert!(sm.contains("#MUSIC:song.mp3;\n"));
+        assert!(sm.contains("#OFFSET:-1.000;\n"));
+        assert!(sm.contains("#BPMS:0.000=120.000;\n"));
+        assert!(sm.ends_with("1000\n0000\n0100\n0000\n0010\n0001\n0000\n0000\n;\n"));
+    }
+
+    #[test]
+    fn test_write_sm_derives_bpm() {
+        // 150 BPM 16ths.
+        let chart = RuntimeChart::new(&[
+            note(300, Direction::Left),
+            note(400, Direction::Down),
+            note(500, Direction::Up),
+            note(600, Direction::Right),
+            note(700, Direction::Left),
+        ]);
+
+        let timing = Timing::from_chart(&chart);
+        assert_eq!(timing.bpms, vec![(0., 150.)]);
+        assert!((timing.offset - 300.).abs() < f64::EPSILON);
+
+        let sm = write_sm(&chart, &metadata(), "song.mp3");
+        assert!(sm.contains("#OFFSET:-0.300;\n"));
+        assert!(sm.contains("1000\n0100\n0010\n0001\n1000\n"));
+    }
+
+    #[test]
+    fn test_write_sm_from_frames() {
+        // 180 BPM 8ths fall every 5 frames, their timestamps alternate 166 and 167 ms apart.
+        let chart = RuntimeChart::new(
+            &(0..8)
+                .map(|i| RuntimeNote {
+                    beat_position: i * 5,
+                    timestamp: ((f64::from(i * 5) / 30.) * 1000.) as u32,
+                    ..note(0, Direction::Left)
+                })
+                .collect::<Vec<_>>(),
+        );
+
+        let timing = Timing::from_chart(&chart);
+        assert_eq!(timing.bpms, vec![(0., 180.)]);
+
+        let sm = write_sm(&chart, &metadata(), "song.mp3");
+        assert!(sm.ends_with("1000\n1000\n1000\n1000\n1000\n1000\n1000\n1000\n;\n"));
+    }
+
+    #[test]
+    fn test_timing_with_bpm_change() {
+        let chart = RuntimeChart::new(&[note(0, Direction::Left), note(3000, Direction::Left)])
+            .with_timing(&[
+                TimingPoint::new(0, 500_000),
+                TimingPoint::new(2000, 250_000),
+            ]);
+
+        let timing = Timing::from_chart(&chart);
+        assert_eq!(timing.bpms, vec![(0., 120.), (4., 240.)]);
+        assert!((timing.beat_at(3000) - 8.).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_write_ssc() {
+        let chart = RuntimeChart::new(&[note(0, Direction::Left)])
+            .with_timing(&[TimingPoint::new(0, 500_000)]);
+
+        let ssc = write_ssc(&chart, &metadata(), "song.mp3");
+        assert!(ssc.starts_with("#VERSION:0.83;\n#TITLE:Title;\n"));
+        assert!(ssc.contains("#STEPSTYPE:dance-single;\n"));
+        assert!(ssc.ends_with("#NOTES:\n1000\n0000\n0000\n0000\n;\n"));
+    }
+
+    #[test]
+    fn test_write_6k() {
+        let mut last = note(500, Direction::Left);
+        last.lane = Lane(5);
+        let chart = RuntimeChart::new(&[note(0, Direction::Left), last])
+            .with_timing(&[TimingPoint::new(0, 500_000)])
+            .with_lane_count(LaneCount::new(6).unwrap());
+
+        let sm = write_sm(&chart, &metadata(), "song.mp3");
+        assert!(sm.contains("     dance-solo:\n"));
+        assert!(sm.ends_with("100000\n000001\n000000\n000000\n;\n"));
+    }
+
+    #[test]
+    fn test_write_mines_and_fakes() {
+        let mut mine = note(500, Direction::Down);
+        mine.kind = NoteKind::Mine;
+        let mut fake = note(500, Direction::Right);
+        fake.kind = NoteKind::Fake;
+        let chart = RuntimeChart::new(&[note(0, Direction::Left), mine, fake])
+            .with_timing(&[TimingPoint::new(0, 500_000)]);
+
+        let sm = write_sm(&chart, &metadata(), "song.mp3");
+        assert!(sm.ends_with("1000\n0M0F\n0000\n0000\n;\n"));
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("a;b:c"), "a\\;b\\:c");
+    }
+}
@@ -51,8 +51,11 @@ use self::{
 };
 use anyhow::Result;
 use btreemultimap::{BTreeMultiMap, MultiRange};
-use rrr_chart::RuntimeNote;
-use rrr_settings_core::{prelude::Direction, CoreSettings};
+use rrr_chart::{NoteKind, RuntimeNote};
+use rrr_settings_core::{
+    prelude::{Lane, LaneCount},
+    CoreSettings,
+};
 use std::{borrow::BorrowMut, collections::HashSet};
 
 pub mod prelude {
@@ -69,6 +72,8 @@ pub struct JudgementReport {
     pub averages: usize,
     pub misses: usize,
     pub boos: usize,
+    /// Mines that were pressed, each one also counts as a miss.
+    pub mines: usize,
 }
 
 #[derive(Debug, Default)]
@@ -94,6 +99,7 @@ pub struct Active {
     actions: BTreeMultiMap<RuntimeNote, NoteAction>,
     judge: Judge,
     misses: HashSet<RuntimeNote>,
+    mines_hit: HashSet<RuntimeNote>,
     judgement_report: JudgementReport,
 }
 
@@ -134,6 +140,7 @@ impl Play<Ready> {
                 actions: BTreeMultiMap::default(),
                 judge: Judge::new(),
                 misses: HashSet::<RuntimeNote>::new(),
+                mines_hit: HashSet::<RuntimeNote>::new(),
                 judgement_report: JudgementReport::default(),
             },
             settings: self.settings,
@@ -148,6 +155,7 @@ impl Play<Ready> {
                 actions: BTreeMultiMap::default(),
                 judge: Judge::new(),
                 misses: HashSet::<RuntimeNote>::new(),
+                mines_hit: HashSet::<RuntimeNote>::new(),
                 judgement_report: JudgementReport::default(),
             },
             settings: self.settings,
@@ -206,6 +214,17 @@ impl Play<Active> {
         &self.state.actions
     }
 
+    #[must_use]
+    pub fn mines_hit(&self) -> &HashSet<RuntimeNote> {
+        &self.state.mines_hit
+    }
+
+    /// How many lanes the chart being played uses.
+    #[must_use]
+    pub fn lane_count(&self) -> LaneCount {
+        self.state.turntable.lane_count()
+    }
+
     pub fn tick(&mut self, progress: u32) {
         self.state.turntable.tick(progress);
         self.check_miss();
@@ -221,6 +240,7 @@ impl Play<Active> {
             let mapped_notes = view
                 .filter(|(&ts, note)| {
                     song_progress >= ts.saturating_add(MISS_OFFSET)
+                        && note.kind.is_judged()
                         && !self.state.misses.contains(note)
                         && !self.state.judge.judgements.contains_key(note)
                 })
@@ -247,16 +267,18 @@ impl Play<Active> {
         &self.state.judge.judgements
     }
 
-    pub fn do_action(&mut self, direction: &Direction, ts: u32, offset: i8) {
+    pub fn do_action(&mut self, lane: Lane, ts: u32, offset: i8) {
+        let chart_ts = ts.saturating_sub(self.state.turntable.sync_offset());
+        let hit_mine = self.hit_mine(lane, chart_ts);
         if let Ok(mut view_result) = self.state.turntable.view(
             120_u32.saturating_add(u32::from(offset.unsigned_abs())),
             120_u32.saturating_add(u32::from(offset.unsigned_abs())),
         ) {
             #[allow(clippy::pattern_type_mismatch)]
             if let Some((_, closest_note)) =
-                view_result.find(|(_, note)| self.determine_judgable(note, direction))
+                view_result.find(|(_, note)| self.determine_judgable(note, lane))
             {
-                if let Ok(judgement_result) = self.state.judge.judge(ts, closest_note) {
+                if let Ok(judgement_result) = self.state.judge.judge(chart_ts, closest_note) {
                     if let Some(judgement) = judgement_result {
                         self.append_to_judgement_report(judgement);
                     } else {
@@ -264,17 +286,44 @@ impl Play<Active> {
                             self.state.judgement_report.boos.saturating_add(1);
                     }
                 }
-            } else {
+            } else if !hit_mine {
                 self.state.judgement_report.boos =
                     self.state.judgement_report.boos.saturating_add(1);
             }
         }
     }
 
-    fn determine_judgable(&self, note: &RuntimeNote, direction: &Direction) -> bool {
+    /// Penalize pressing `lane` while a mine on it is passing the receptor.
+    fn hit_mine(&mut self, lane: Lane, chart_ts: u32) -> bool {
+        const MINE_WINDOW: u32 = 50;
+
+        let Ok(view) = self.state.turntable.view(MINE_WINDOW, MINE_WINDOW) else {
+            return false;
+        };
+
+        #[allow(clippy::pattern_type_mismatch)]
+        let mine = view
+            .filter(|(_, note)| note.kind == NoteKind::Mine && note.lane == lane)
+            .find(|(&ts, note)| {
+                ts.abs_diff(chart_ts) <= MINE_WINDOW && !self.state.mines_hit.contains(note)
+            })
+            .map(|(_, note)| note.clone());
+
+        if let Some(mine) = mine {
+            self.state.mines_hit.insert(mine);
+            let report = self.state.judgement_report.borrow_mut();
+            report.mines = report.mines.saturating_add(1);
+            report.misses = report.misses.saturating_add(1);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn determine_judgable(&self, note: &RuntimeNote, lane: Lane) -> bool {
         let is_judged = self.state.actions.contains_key(note);
-        let is_same_direction = *direction == note.direction;
-        !is_judged && is_same_direction
+        let is_same_lane = lane == note.lane;
+        note.kind.is_judged() && !is_judged && is_same_lane
     }
 
     fn append_to_judgement_report(&mut self, judgement: JudgeWindow) {
@@ -334,7 +383,10 @@ impl Difference for u32 {
 // tests
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use rrr_chart::RuntimeChart;
     use rrr_math::lerp::Lerp;
+    use rrr_record::record::Record;
 
     fn screen_pos_to_lerp_time() -> f64 {
         (-64.).inv_lerp(720., 64.)
@@ -373,6 +425,41 @@ mod tests {
             (ms_time_from_screen_height_time_on_screen_and_position() - 490.0).abs() < f64::EPSILON
         );
     }
+
+    fn note(lane: u8, kind: NoteKind) -> RuntimeNote {
+        RuntimeNote {
+            beat_position: 30,
+            color: rrr_chart::NoteColor::Red,
+            lane: Lane(lane),
+            timestamp: 1000,
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_mines_and_fakes() -> Result<()> {
+        let chart = RuntimeChart::new(&[
+            note(0, NoteKind::Tap),
+            note(1, NoteKind::Mine),
+            note(2, NoteKind::Fake),
+        ]);
+        let record = Record::new(Vec::new(), chart)?;
+        let mut play = Play::new(Turntable::load(record)).start();
+
+        play.tick(1000);
+        play.do_action(Lane(1), 1000, 0);
+        play.do_action(Lane(1), 1010, 0);
+        play.do_action(Lane(2), 1000, 0);
+        play.tick(1300);
+
+        let report = play.judgement_results();
+        assert_eq!(report.mines, 1);
+        assert_eq!(report.boos, 2);
+        // The missed tap and the pressed mine.
+        assert_eq!(report.misses, 2);
+        assert!(play.missed_notes().contains(&note(0, NoteKind::Tap)));
+        Ok(())
+    }
 }
 
 // What I should really be doing is determining exactly what ratio is between this zero point and the note.
@@ -3,6 +3,7 @@ use btreemultimap::MultiRange;
 use rrr_audio::AudioPlayer;
 use rrr_chart::RuntimeNote;
 use rrr_record::record::Record;
+use rrr_settings_core::prelude::LaneCount;
 use std::{borrow::BorrowMut, ops::Bound::Included};
 
 #[derive(Debug)]
@@ -21,6 +22,8 @@ pub struct Loaded {}
 pub struct Playing {
     pub progress: u32,
     audio_player: Option<AudioPlayer>,
+    /// Subtracted from progress so the chart lines up with late audio.
+    sync_offset: u32,
 }
 
 pub trait TurntableState {}
@@ -47,6 +50,7 @@ impl Turntable<Loaded> {
             state: Playing {
                 progress: 0,
                 audio_player: None,
+                sync_offset: 0,
             },
         }
     }
@@ -63,11 +67,13 @@ impl Turntable<Loaded> {
             state: Playing {
                 progress: 0,
                 audio_player: None,
+                sync_offset: 0,
             },
         };
 
         if let Ok(audio_player) = AudioPlayer::try_new(turntable.record.mp3.as_slice()) {
             turntable.state.audio_player.replace(audio_player);
+            turntable.state.sync_offset = turntable.record.sync_offset();
         }
 
         turntable
@@ -88,7 +94,7 @@ impl Turntable<Playing> {
     }
 
     pub fn tick(&mut self, progress: u32) {
-        self.state.progress = progress;
+        self.state.progress = progress.saturating_sub(self.state.sync_offset);
 
         #[allow(clippy::pattern_type_mismatch)]
         if let Some(player) = self.state.audio_player.borrow_mut() {
@@ -106,6 +112,17 @@ impl Turntable<Playing> {
         self.state.progress
     }
 
+    #[must_use]
+    pub fn lane_count(&self) -> LaneCount {
+        self.record.chart.lane_count
+    }
+
+    /// How far behind the audio the chart is held, taken from the record's audio latency.
+    #[must_use]
+    pub fn sync_offset(&self) -> u32 {
+        self.state.sync_offset
+    }
+
     /// .
     ///
     /// # Errors
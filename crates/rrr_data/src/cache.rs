@@ -0,0 +1,349 @@
+//! Downloads kept on disk so playing a song again doesn't fetch it again.
+//!
+//! Entries are stored by key, the chart's `hash_load` or the playlist's URL, in files named
+//! after the SHA-256 of that key. Each file starts with the SHA-256 of its contents, entries
+//! that no longer match it are removed instead of being returned. Once the cache grows past
+//! its size limit the least recently used entries are removed first.
+//!
+//! Charts the user downloaded on purpose are [pinned](Cache::pin), pinned entries are kept in
+//! their own directory and are never evicted.
+
+use crate::APP_INFO;
+use app_dirs::{AppDataType, AppDirsError};
+use filetime::FileTime;
+use sha2::{Digest, Sha256};
+use std::{
+    fmt::Write,
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use thiserror::Error;
+
+const DIGEST_LEN: usize = 32;
+const EXTENSION: &str = "cache";
+const PINNED_DIR: &str = "pinned";
+
+#[derive(Error, Debug)]
+pub enum CacheError {
+    #[error("No cache directory: {0}")]
+    Dir(#[from] AppDirsError),
+
+    #[error("Could not use the cache: {0}")]
+    Io(#[from] io::Error),
+}
+
+#[derive(Clone, Debug)]
+pub struct Cache {
+    dir: PathBuf,
+    max_size: u64,
+}
+
+impl Cache {
+    /// The size limit unless [`Cache::with_max_size`] is used, 512 MiB.
+    pub const DEFAULT_MAX_SIZE: u64 = 512 * 1024 * 1024;
+
+    /// The cache in the user's cache directory.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`CacheError`] if the platform has no cache directory or it can't be created.
+    pub fn open() -> Result<Self, CacheError> {
+        let dir = app_dirs::app_dir(AppDataType::UserCache, &APP_INFO, "downloads")?;
+        Ok(Self::at(dir))
+    }
+
+    /// A cache in `dir`, which is created when the first entry is stored.
+    #[must_use]
+    pub fn at(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            max_size: Self::DEFAULT_MAX_SIZE,
+        }
+    }
+
+    #[must_use]
+    pub fn with_max_size(self, max_size: u64) -> Self {
+        Self { max_size, ..self }
+    }
+
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The data stored under `key`, if it is there and intact.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let pinned = self.pinned_path(key);
+        let path = if pinned.exists() {
+            pinned
+        } else {
+            self.path(key)
+        };
+        let file = fs::read(&path).ok()?;
+
+        if file.len() < DIGEST_LEN {
+            return None;
+        }
+        let (digest, data) = file.split_at(DIGEST_LEN);
+        if digest != Sha256::digest(data).as_slice() {
+            log::warn!("Removing corrupted cache entry {}.", path.display());
+            let _ = fs::remove_file(&path);
+            return None;
+        }
+
+        // Eviction goes by modification time, so reading an entry counts as using it.
+        let _ = filetime::set_file_mtime(&path, FileTime::now());
+
+        Some(data.to_vec())
+    }
+
+    /// Store `data` under `key`, replacing what was there, then evict down to the size limit.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`CacheError`] if the entry can't be written.
+    pub fn put(&self, key: &str, data: &[u8]) -> Result<(), CacheError> {
+        let pinned = self.pinned_path(key);
+        if pinned.exists() {
+            return write_entry(&pinned, data);
+        }
+
+        write_entry(&self.path(key), data)?;
+        self.evict()
+    }
+
+    /// Store `data` under `key` like [`Cache::put`], pinned so it's never evicted.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`CacheError`] if the entry can't be written.
+    pub fn put_pinned(&self, key: &str, data: &[u8]) -> Result<(), CacheError> {
+        write_entry(&self.pinned_path(key), data)?;
+        remove_entry(&self.path(key))
+    }
+
+    /// Keep the entry under `key` from being evicted, returns whether there is one.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`CacheError`] if the entry can't be moved.
+    pub fn pin(&self, key: &str) -> Result<bool, CacheError> {
+        let pinned = self.pinned_path(key);
+        if pinned.exists() {
+            return Ok(true);
+        }
+
+        fs::create_dir_all(self.dir.join(PINNED_DIR))?;
+        match fs::rename(self.path(key), &pinned) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn remove(&self, key: &str) {
+        let _ = fs::remove_file(self.path(key));
+        let _ = fs::remove_file(self.pinned_path(key));
+    }
+
+    /// Total size of every entry that can be evicted, in bytes.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`CacheError`] if the cache directory can't be read.
+    pub fn size(&self) -> Result<u64, CacheError> {
+        Ok(self.entries()?.iter().map(|entry| entry.size).sum())
+    }
+
+    fn evict(&self) -> Result<(), CacheError> {
+        let mut entries = self.entries()?;
+        let mut size: u64 = entries.iter().map(|entry| entry.size).sum();
+
+        entries.sort_by_key(|entry| entry.used);
+        for entry in entries {
+            if size <= self.max_size {
+                break;
+            }
+
+            // Another cache using the same directory may have evicted it first.
+            remove_entry(&entry.path)?;
+            size = size.saturating_sub(entry.size);
+        }
+
+        Ok(())
+    }
+
+    fn entries(&self) -> Result<Vec<Entry>, CacheError> {
+        let read_dir = match fs::read_dir(&self.dir) {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut entries = Vec::new();
+        for dir_entry in read_dir {
+            let path = dir_entry?.path();
+            if path.extension().and_then(|extension| extension.to_str()) != Some(EXTENSION) {
+                continue;
+            }
+
+            let metadata = match fs::metadata(&path) {
+                Ok(metadata) => metadata,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            entries.push(Entry {
+                size: metadata.len(),
+                used: metadata.modified()?,
+                path,
+            });
+        }
+
+        Ok(entries)
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(file_name(key))
+    }
+
+    fn pinned_path(&self, key: &str) -> PathBuf {
+        self.dir.join(PINNED_DIR).join(file_name(key))
+    }
+}
+
+fn file_name(key: &str) -> String {
+    let digest = Sha256::digest(key.as_bytes())
+        .iter()
+        .fold(String::new(), |mut name, byte| {
+            let _ = write!(name, "{byte:02x}");
+            name
+        });
+    format!("{digest}.{EXTENSION}")
+}
+
+fn write_entry(path: &Path, data: &[u8]) -> Result<(), CacheError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut file = Vec::with_capacity(DIGEST_LEN.saturating_add(data.len()));
+    file.extend_from_slice(&Sha256::digest(data));
+    file.extend_from_slice(data);
+
+    // Written aside and renamed into place so a reader never sees half an entry.
+    let partial = path.with_extension("partial");
+    fs::write(&partial, &file)?;
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
+fn remove_entry(path: &Path) -> Result<(), CacheError> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+struct Entry {
+    path: PathBuf,
+    size: u64,
+    used: SystemTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    /// The cache is removed when the directory is dropped.
+    fn temp_cache(max_size: u64) -> Result<(TempDir, Cache), CacheError> {
+        let dir = TempDir::new()?;
+        let cache = Cache::at(dir.path().to_path_buf()).with_max_size(max_size);
+        Ok((dir, cache))
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<(), CacheError> {
+        let (_dir, cache) = temp_cache(Cache::DEFAULT_MAX_SIZE)?;
+        assert_eq!(cache.get("8e058c682f875b36fac6fa065c33fd88"), None);
+
+        cache.put("8e058c682f875b36fac6fa065c33fd88", b"chart")?;
+        cache.put("https://example.com/playlist", b"playlist")?;
+        assert_eq!(
+            cache.get("8e058c682f875b36fac6fa065c33fd88"),
+            Some(b"chart".to_vec())
+        );
+        assert_eq!(
+            cache.get("https://example.com/playlist"),
+            Some(b"playlist".to_vec())
+        );
+
+        cache.remove("https://example.com/playlist");
+        assert_eq!(cache.get("https://example.com/playlist"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejects_corrupted_entry() -> Result<(), CacheError> {
+        let (_dir, cache) = temp_cache(Cache::DEFAULT_MAX_SIZE)?;
+        cache.put("chart", b"chart")?;
+
+        let path = cache.path("chart");
+        let mut file = fs::read(&path)?;
+        if let Some(byte) = file.last_mut() {
+            *byte ^= 0xff;
+        }
+        fs::write(&path, file)?;
+
+        assert_eq!(cache.get("chart"), None);
+        assert!(!path.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() -> Result<(), CacheError> {
+        let entry_size = u64::try_from(DIGEST_LEN).unwrap_or(u64::MAX) + 100;
+        let (_dir, cache) = temp_cache(entry_size * 2)?;
+
+        let data = [0_u8; 100];
+        cache.put("first", &data)?;
+        cache.put("second", &data)?;
+
+        // Modification times can be coarse, make the order unambiguous.
+        let past = SystemTime::now() - Duration::from_secs(60);
+        filetime::set_file_mtime(cache.path("second"), FileTime::from_system_time(past))?;
+
+        cache.put("third", &data)?;
+        assert_eq!(cache.size()?, entry_size * 2);
+        assert!(cache.get("first").is_some());
+        assert!(cache.get("second").is_none());
+        assert!(cache.get("third").is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pinned_entries_are_not_evicted() -> Result<(), CacheError> {
+        let entry_size = u64::try_from(DIGEST_LEN).unwrap_or(u64::MAX) + 100;
+        let (_dir, cache) = temp_cache(entry_size)?;
+
+        let data = [0_u8; 100];
+        cache.put_pinned("downloaded", &data)?;
+        cache.put("played", &data)?;
+        assert!(!cache.pin("missing")?);
+        assert!(cache.pin("played")?);
+        cache.put("next", &data)?;
+        cache.put("downloaded", b"updated")?;
+
+        assert_eq!(cache.size()?, entry_size);
+        assert_eq!(cache.get("downloaded"), Some(b"updated".to_vec()));
+        assert!(cache.get("played").is_some());
+        assert!(cache.get("next").is_some());
+
+        cache.remove("played");
+        assert_eq!(cache.get("played"), None);
+        Ok(())
+    }
+}
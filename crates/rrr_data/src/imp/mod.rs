@@ -1,5 +1,10 @@
-use serde::{Deserialize, Serialize};
-use uuid::Uuid;
+//! Small pieces of user data, such as playlists, kept between sessions.
+//!
+//! Natively each key is a JSON file in the user's data directory, on the web it is an entry
+//! in local storage.
+
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
 
 #[cfg(target_arch = "wasm32")]
 mod platform {
@@ -13,16 +18,44 @@ mod platform {
     pub use self::native::*;
 }
 
-pub fn write<'a, T>(id: Uuid, data: T) -> bool
-where
-    T: Serialize + Deserialize<'a>,
-{
-    platform::write(id, data)
+#[derive(Error, Debug)]
+pub enum DataError {
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("No data directory: {0}")]
+    Dir(#[from] app_dirs::AppDirsError),
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("Could not use the data directory: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("Invalid data in {key}: {source}")]
+    Json {
+        key: String,
+        source: serde_json::Error,
+    },
+
+    #[cfg(target_arch = "wasm32")]
+    #[error("Could not use local storage: {0}")]
+    Storage(#[from] gloo_storage::errors::StorageError),
+}
+
+/// Store `data` under `key`, replacing what was there.
+///
+/// Keys are plain names such as `playlists`, they are used as file names.
+///
+/// # Errors
+///
+/// Will return [`DataError`] if the data can't be stored.
+pub fn write<T: Serialize>(key: &str, data: &T) -> Result<(), DataError> {
+    platform::write(key, data)
 }
 
-pub fn read<'a, T>(id: Uuid, data: T) -> bool
-where
-    T: Serialize + Deserialize<'a>,
-{
-    platform::read(id, data)
+/// The data stored under `key`, or `None` if nothing is.
+///
+/// # Errors
+///
+/// Will return [`DataError`] if the data can't be read or isn't a `T`.
+pub fn read<T: DeserializeOwned>(key: &str) -> Result<Option<T>, DataError> {
+    platform::read(key)
 }
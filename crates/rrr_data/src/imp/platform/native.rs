@@ -1,17 +1,72 @@
-use serde::{Deserialize, Serialize};
+use crate::{DataError, APP_INFO};
+use app_dirs::AppDataType;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
 
-use uuid::Uuid;
+pub fn write<T: Serialize>(key: &str, data: &T) -> Result<(), DataError> {
+    write_in(&data_dir()?, key, data)
+}
+
+pub fn read<T: DeserializeOwned>(key: &str) -> Result<Option<T>, DataError> {
+    read_in(&data_dir()?, key)
+}
+
+fn data_dir() -> Result<PathBuf, DataError> {
+    Ok(app_dirs::app_dir(AppDataType::UserData, &APP_INFO, "data")?)
+}
+
+fn write_in<T: Serialize>(dir: &Path, key: &str, data: &T) -> Result<(), DataError> {
+    let json = serde_json::to_vec_pretty(data).map_err(|source| DataError::Json {
+        key: key.to_string(),
+        source,
+    })?;
 
-pub fn write<'a, T>(_id: Uuid, _data: T) -> bool
-where
-    T: Serialize + Deserialize<'a>,
-{
-    false
+    // Written aside and renamed into place so a crash never leaves half a file.
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{key}.json"));
+    let partial = path.with_extension("partial");
+    fs::write(&partial, json)?;
+    fs::rename(&partial, &path)?;
+    Ok(())
 }
 
-pub fn read<'a, T>(_id: Uuid, _data: T) -> bool
-where
-    T: Serialize + Deserialize<'a>,
-{
-    false
+fn read_in<T: DeserializeOwned>(dir: &Path, key: &str) -> Result<Option<T>, DataError> {
+    let json = match fs::read(dir.join(format!("{key}.json"))) {
+        Ok(json) => json,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    serde_json::from_slice(&json)
+        .map(Some)
+        .map_err(|source| DataError::Json {
+            key: key.to_string(),
+            source,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_round_trip() -> Result<(), DataError> {
+        let temp = TempDir::new()?;
+        let dir = temp.path().join("data");
+
+        assert_eq!(read_in::<Vec<u16>>(&dir, "levels")?, None);
+        write_in(&dir, "levels", &vec![1_u16, 7])?;
+        assert_eq!(read_in::<Vec<u16>>(&dir, "levels")?, Some(vec![1, 7]));
+
+        fs::write(dir.join("levels.json"), "[1,")?;
+        assert!(matches!(
+            read_in::<Vec<u16>>(&dir, "levels"),
+            Err(DataError::Json { .. })
+        ));
+        Ok(())
+    }
 }
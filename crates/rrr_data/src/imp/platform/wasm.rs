@@ -1,17 +1,18 @@
-use serde::{Deserialize, Serialize};
+use crate::DataError;
+use gloo_storage::{errors::StorageError, LocalStorage, Storage};
+use serde::{de::DeserializeOwned, Serialize};
 
-use uuid::Uuid;
+/// Keeps our entries apart from anything else on the same origin.
+const PREFIX: &str = "rrr.";
 
-pub fn write<'a, T>(_id: Uuid, _data: T) -> bool
-where
-    T: Serialize + Deserialize<'a>,
-{
-    false
+pub fn write<T: Serialize>(key: &str, data: &T) -> Result<(), DataError> {
+    Ok(LocalStorage::set(format!("{PREFIX}{key}"), data)?)
 }
 
-pub fn read<'a, T>(_id: Uuid, _data: T) -> bool
-where
-    T: Serialize + Deserialize<'a>,
-{
-    false
+pub fn read<T: DeserializeOwned>(key: &str) -> Result<Option<T>, DataError> {
+    match LocalStorage::get(format!("{PREFIX}{key}")) {
+        Ok(data) => Ok(Some(data)),
+        Err(StorageError::KeyNotFound(_)) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
 }
@@ -1,2 +1,10 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cache;
 mod imp;
 pub use crate::imp::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+const APP_INFO: app_dirs::AppInfo = app_dirs::AppInfo {
+    name: "rrr",
+    author: "flashflashrevolution",
+};
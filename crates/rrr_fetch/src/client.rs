@@ -0,0 +1,218 @@
+//! Typed requests for FFR's JSON endpoints.
+
+use crate::{ffr::Endpoints, platform::fetch_all, FetchError, ManifestPayload, PlaylistPayload};
+use rrr_data::cache::Cache;
+use rrr_playlist::FFRPlaylist;
+use serde::de::DeserializeOwned;
+
+/// Every artist and chart, `None` without a request if there's no
+/// [`Endpoints::manifest_path`].
+///
+/// # Errors
+///
+/// Will return [`FetchError`] if the request fails or the manifest is malformed.
+pub fn fetch_manifest(endpoints: &Endpoints) -> Result<Option<ManifestPayload>, FetchError> {
+    endpoints.manifest_url().map(fetch_json).transpose()
+}
+
+/// Every artist and chart, kept in `cache` for [`cached_manifest`]. Like [`fetch_manifest`]
+/// it's `None` if there's no [`Endpoints::manifest_path`].
+///
+/// # Errors
+///
+/// Will return [`FetchError`] if the request fails or the manifest is malformed, not being
+/// able to cache it is only logged.
+pub fn fetch_and_cache_manifest(
+    cache: &Cache,
+    endpoints: &Endpoints,
+) -> Result<Option<ManifestPayload>, FetchError> {
+    let Some(url) = endpoints.manifest_url() else {
+        return Ok(None);
+    };
+    let data = fetch_all(url.clone())?;
+    let manifest = decode(url.clone(), &data)?;
+
+    if let Err(e) = cache.put(&url, &data) {
+        log::warn!("Could not cache the manifest: {e}");
+    }
+    Ok(Some(manifest))
+}
+
+/// The manifest from the last time [`fetch_and_cache_manifest`] fetched it, for playing
+/// offline.
+#[must_use]
+pub fn cached_manifest(cache: &Cache, endpoints: &Endpoints) -> Option<ManifestPayload> {
+    let data = cache.get(&endpoints.manifest_url()?)?;
+    serde_json::from_slice(&data).ok()
+}
+
+/// Every song.
+///
+/// # Errors
+///
+/// Will return [`FetchError`] if the request fails or the playlist is malformed.
+pub fn fetch_playlist(endpoints: &Endpoints) -> Result<PlaylistPayload, FetchError> {
+    fetch_json(endpoints.playlist_url())
+}
+
+/// Every song, kept in `cache` for [`cached_playlist`].
+///
+/// # Errors
+///
+/// Will return [`FetchError`] if the request fails or the playlist is malformed, not being
+/// able to cache it is only logged.
+pub fn fetch_and_cache_playlist(
+    cache: &Cache,
+    endpoints: &Endpoints,
+) -> Result<FFRPlaylist, FetchError> {
+    let url = endpoints.playlist_url();
+    let data = fetch_all(url.clone())?;
+    let playlist = FFRPlaylist::from_json(&data).map_err(|error| FetchError::Decode {
+        url: url.clone(),
+        message: error.to_string(),
+    })?;
+
+    if let Err(e) = cache.put(&url, &data) {
+        log::warn!("Could not cache the playlist: {e}");
+    }
+    Ok(playlist)
+}
+
+/// The playlist from the last time [`fetch_and_cache_playlist`] fetched it, for playing
+/// offline.
+#[must_use]
+pub fn cached_playlist(cache: &Cache, endpoints: &Endpoints) -> Option<FFRPlaylist> {
+    let data = cache.get(&endpoints.playlist_url())?;
+    FFRPlaylist::from_json(&data).ok()
+}
+
+fn fetch_json<T: DeserializeOwned>(url: String) -> Result<T, FetchError> {
+    let data = fetch_all(url.clone())?;
+    decode(url, &data)
+}
+
+fn decode<T: DeserializeOwned>(url: String, data: &[u8]) -> Result<T, FetchError> {
+    serde_json::from_slice(data).map_err(|error| FetchError::Decode {
+        url,
+        message: error.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stand_in::StandIn;
+    use rrr_types::SongID;
+    use tempfile::TempDir;
+
+    // Written by hand from the fields the payloads read, not captured from FFR.
+    const MANIFEST: &[u8] = include_bytes!("test_assets/manifest.json");
+    const PLAYLIST: &[u8] = include_bytes!("test_assets/playlist.json");
+
+    fn stand_in() -> StandIn {
+        StandIn::serve(&[
+            ("/r3-manifest.php", MANIFEST),
+            ("/r3-playlist.v2.php", PLAYLIST),
+        ])
+    }
+
+    fn endpoints(stand_in: &StandIn) -> Endpoints {
+        Endpoints {
+            manifest_path: Some("r3-manifest.php".to_string()),
+            ..stand_in.endpoints()
+        }
+    }
+
+    #[test]
+    fn test_fetch_manifest() -> Result<(), FetchError> {
+        let stand_in = stand_in();
+        let manifest = fetch_manifest(&endpoints(&stand_in))?.expect("manifest_path is set");
+
+        assert_eq!(manifest.charts.len(), 2);
+        let artist = manifest.artist_of(&SongID(7));
+        assert_eq!(
+            artist.map(|artist| artist.name.as_str()),
+            Some("Ludicrous Speed")
+        );
+        assert_eq!(
+            manifest
+                .chart(&SongID(1))
+                .map(|chart| chart.releasedate.as_str()),
+            Some("2007-10-02")
+        );
+        assert!(manifest.chart(&SongID(2)).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_manifest_path() -> Result<(), FetchError> {
+        let dir = TempDir::new().unwrap();
+        let cache = Cache::at(dir.path().to_path_buf());
+        let stand_in = stand_in();
+
+        assert_eq!(fetch_manifest(&stand_in.endpoints())?, None);
+        assert_eq!(
+            fetch_and_cache_manifest(&cache, &stand_in.endpoints())?,
+            None
+        );
+        assert_eq!(cached_manifest(&cache, &stand_in.endpoints()), None);
+        assert!(stand_in.requests().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_fetch_playlist() -> Result<(), FetchError> {
+        let playlist: FFRPlaylist = fetch_playlist(&stand_in().endpoints())?.into();
+
+        assert_eq!(
+            playlist
+                .song(&SongID(1))
+                .map(|song| song.hash_load.as_str()),
+            Some("8e058c682f875b36fac6fa065c33fd88")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_cached_playlist() -> Result<(), FetchError> {
+        let dir = TempDir::new().unwrap();
+        let cache = Cache::at(dir.path().to_path_buf());
+        let stand_in = stand_in();
+        let endpoints = stand_in.endpoints();
+        assert_eq!(cached_playlist(&cache, &endpoints), None);
+
+        let playlist = fetch_and_cache_playlist(&cache, &endpoints)?;
+        drop(stand_in);
+        assert_eq!(cached_playlist(&cache, &endpoints), Some(playlist));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cached_manifest() -> Result<(), FetchError> {
+        let dir = TempDir::new().unwrap();
+        let cache = Cache::at(dir.path().to_path_buf());
+        let stand_in = stand_in();
+        let endpoints = endpoints(&stand_in);
+        assert_eq!(cached_manifest(&cache, &endpoints), None);
+
+        let manifest = fetch_and_cache_manifest(&cache, &endpoints)?;
+        assert!(manifest.is_some());
+        drop(stand_in);
+        assert_eq!(cached_manifest(&cache, &endpoints), manifest);
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors() {
+        let stand_in = StandIn::serve(&[("/r3-manifest.php", b"{ \"artists\": [] }")]);
+
+        assert!(matches!(
+            fetch_manifest(&endpoints(&stand_in)),
+            Err(FetchError::Decode { .. })
+        ));
+        assert!(matches!(
+            fetch_playlist(&stand_in.endpoints()),
+            Err(FetchError::Status { code: 404, .. })
+        ));
+    }
+}
@@ -0,0 +1,258 @@
+//! Downloading many charts into the cache at once, such as every song in the playlist.
+//!
+//! Charts already in the cache are skipped, so running a cancelled or failed download again
+//! picks up where it stopped. Downloaded charts are [pinned](Cache::pin) so playing other
+//! songs never evicts them.
+
+use crate::{ffr::Endpoints, platform::Fetcher, FetchError, FetchProgress};
+use rrr_data::cache::{Cache, CacheError};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+use thiserror::Error;
+
+/// Longest a download waits to retry before checking whether it was cancelled.
+const CANCEL_POLL: Duration = Duration::from_millis(100);
+
+#[derive(Error, Debug)]
+pub enum DownloadError {
+    #[error(transparent)]
+    Fetch(#[from] FetchError),
+
+    #[error(transparent)]
+    Cache(#[from] CacheError),
+}
+
+/// Stops a download from another thread, see [`DownloadManager::cancel_handle`].
+#[derive(Clone, Debug, Default)]
+pub struct CancelHandle(Arc<AtomicBool>);
+
+impl CancelHandle {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// How many of the charts asked for have been dealt with so far.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DownloadProgress {
+    pub total: usize,
+    /// Already in the cache, so not downloaded again.
+    pub cached: usize,
+    pub downloaded: usize,
+    pub failed: usize,
+}
+
+impl DownloadProgress {
+    #[must_use]
+    pub fn finished(&self) -> usize {
+        self.cached
+            .saturating_add(self.downloaded)
+            .saturating_add(self.failed)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DownloadReport {
+    pub progress: DownloadProgress,
+    /// The `hash_load` of each chart that failed, and why.
+    pub failures: Vec<(String, DownloadError)>,
+    pub cancelled: bool,
+}
+
+#[derive(Debug)]
+pub struct DownloadManager {
+    cache: Cache,
+    endpoints: Endpoints,
+    concurrency: usize,
+    cancel: CancelHandle,
+}
+
+enum Outcome {
+    Cached,
+    Downloaded,
+}
+
+impl DownloadManager {
+    pub const DEFAULT_CONCURRENCY: usize = 4;
+
+    /// Downloads charts from `endpoints` into `cache`.
+    #[must_use]
+    pub fn new(cache: Cache, endpoints: Endpoints) -> Self {
+        Self {
+            cache,
+            endpoints,
+            concurrency: Self::DEFAULT_CONCURRENCY,
+            cancel: CancelHandle::default(),
+        }
+    }
+
+    /// Download at most `concurrency` charts at a time, at least one.
+    #[must_use]
+    pub fn with_concurrency(self, concurrency: usize) -> Self {
+        Self {
+            concurrency: concurrency.max(1),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone()
+    }
+
+    /// Download the chart of every `hash_load` in `hashes` that isn't cached yet, calling
+    /// `on_progress` on this thread as each one finishes.
+    ///
+    /// Cancelling stops the charts in flight without counting them, they aren't cached.
+    pub fn download(
+        &self,
+        hashes: &[String],
+        mut on_progress: impl FnMut(&DownloadProgress),
+    ) -> DownloadReport {
+        let mut report = DownloadReport {
+            progress: DownloadProgress {
+                total: hashes.len(),
+                ..DownloadProgress::default()
+            },
+            ..DownloadReport::default()
+        };
+
+        let queue = Mutex::new(hashes.iter());
+        let queue = &queue;
+        let (sender, receiver) = mpsc::channel();
+        thread::scope(|scope| {
+            for _ in 0..self.concurrency.min(hashes.len()) {
+                let sender = sender.clone();
+                scope.spawn(move || loop {
+                    let next = queue.lock().ok().and_then(|mut queue| queue.next());
+                    let Some(hash) = next else {
+                        break;
+                    };
+                    if self.cancel.is_cancelled() {
+                        break;
+                    }
+
+                    let outcome = self.download_one(hash);
+                    if sender.send((hash, outcome)).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(sender);
+
+            for (hash, outcome) in receiver {
+                let progress = &mut report.progress;
+                match outcome {
+                    Ok(Outcome::Cached) => progress.cached += 1,
+                    Ok(Outcome::Downloaded) => progress.downloaded += 1,
+                    Err(DownloadError::Fetch(FetchError::Cancelled { .. })) => continue,
+                    Err(error) => {
+                        log::warn!("Could not download {hash}: {error}");
+                        progress.failed += 1;
+                        report.failures.push((hash.clone(), error));
+                    }
+                }
+                on_progress(&report.progress);
+            }
+        });
+
+        report.cancelled = self.cancel.is_cancelled();
+        report
+    }
+
+    fn download_one(&self, hash: &str) -> Result<Outcome, DownloadError> {
+        if self.cache.pin(hash)? && self.cache.get(hash).is_some() {
+            return Ok(Outcome::Cached);
+        }
+
+        let mut fetcher = Fetcher::new(self.endpoints.chart_url(hash))?;
+        loop {
+            if self.cancel.is_cancelled() {
+                fetcher.cancel();
+            }
+            if let Some(wait) = fetcher.retry_in() {
+                // Keep noticing cancellation while waiting to retry.
+                thread::sleep(wait.min(CANCEL_POLL));
+            }
+            if let FetchProgress::Finished = fetcher.fetch()? {
+                break;
+            }
+        }
+
+        self.cache.put_pinned(hash, &fetcher.consume())?;
+        Ok(Outcome::Downloaded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stand_in::StandIn;
+    use tempfile::TempDir;
+
+    /// The cache is removed when the directory is dropped.
+    fn temp_cache() -> (TempDir, Cache) {
+        let dir = TempDir::new().unwrap();
+        let cache = Cache::at(dir.path().to_path_buf());
+        (dir, cache)
+    }
+
+    #[test]
+    fn test_download() -> Result<(), CacheError> {
+        let (_dir, cache) = temp_cache();
+        cache.put("cached", b"cached")?;
+
+        let stand_in = StandIn::serve(&[
+            ("/r3-songLoad.php?id=first&type=ChartFFR_music", b"first"),
+            ("/r3-songLoad.php?id=second&type=ChartFFR_music", b"second"),
+        ]);
+        let hashes = ["first", "cached", "second"].map(String::from);
+        let manager = DownloadManager::new(cache.clone(), stand_in.endpoints()).with_concurrency(2);
+        let mut updates = Vec::new();
+        let report = manager.download(&hashes, |progress| updates.push(*progress));
+
+        assert_eq!(
+            report.progress,
+            DownloadProgress {
+                total: 3,
+                cached: 1,
+                downloaded: 2,
+                failed: 0,
+            }
+        );
+        assert_eq!(updates.len(), 3);
+        assert!(!report.cancelled);
+        assert_eq!(cache.get("first"), Some(b"first".to_vec()));
+        assert_eq!(cache.get("second"), Some(b"second".to_vec()));
+        // Every chart is pinned, none of them count towards the size limit.
+        assert_eq!(cache.size()?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cancel() {
+        let (_dir, cache) = temp_cache();
+        // Nothing is listening, any request would fail instead of being skipped.
+        let manager = DownloadManager::new(
+            cache.clone(),
+            Endpoints::with_base_url("http://127.0.0.1:1/"),
+        );
+        manager.cancel_handle().cancel();
+
+        let report = manager.download(&["first".to_string()], |_| {});
+        assert!(report.cancelled);
+        assert_eq!(report.progress.finished(), 0);
+        assert!(report.failures.is_empty());
+    }
+}
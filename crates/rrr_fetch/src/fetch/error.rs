@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Why fetching a URL failed, the same on every platform.
+#[derive(Error, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FetchError {
+    #[error("Could not reach {url}: {message}")]
+    Network { url: String, message: String },
+
+    /// The URL is malformed or its host doesn't resolve.
+    #[error("Could not find {url}: {message}")]
+    Address { url: String, message: String },
+
+    #[error("{url} responded with {code}.")]
+    Status { url: String, code: u16 },
+
+    #[error("{url} did not say how long its response is.")]
+    MissingLength { url: String },
+
+    #[error("Could not read the response from {url}: {message}")]
+    Decode { url: String, message: String },
+
+    #[error("Fetching {url} was cancelled.")]
+    Cancelled { url: String },
+}
+
+impl FetchError {
+    /// Whether trying again later could succeed, such as after a dropped connection or
+    /// while the server is overloaded.
+    #[must_use]
+    pub fn is_transient(&self) -> bool {
+        match self {
+            FetchError::Network { .. } => true,
+            FetchError::Status { code, .. } => matches!(code, 408 | 429 | 500 | 502 | 503 | 504),
+            FetchError::Address { .. }
+            | FetchError::MissingLength { .. }
+            | FetchError::Decode { .. }
+            | FetchError::Cancelled { .. } => false,
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl From<FetchError> for wasm_bindgen::JsValue {
+    fn from(error: FetchError) -> Self {
+        js_sys::Error::new(&error.to_string()).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transient() {
+        let url = "http://127.0.0.1/".to_string();
+        let status = |code| FetchError::Status {
+            url: url.clone(),
+            code,
+        };
+
+        assert!(status(503).is_transient());
+        assert!(!status(404).is_transient());
+        assert!(FetchError::Network {
+            url: url.clone(),
+            message: "Connection reset".to_string(),
+        }
+        .is_transient());
+        assert!(!FetchError::Address {
+            url: url.clone(),
+            message: "Dns Failed".to_string(),
+        }
+        .is_transient());
+        assert!(!FetchError::Cancelled { url }.is_transient());
+    }
+}
@@ -1,113 +1,410 @@
 #![allow(dead_code)]
 
-use crate::FetchProgress;
-use anyhow::Result;
+use crate::{FetchError, FetchProgress};
+use rrr_data::cache::Cache;
 use std::fmt::Debug;
-use std::io::Read;
+use std::io::{ErrorKind, Read};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Most bytes read per call to [`Fetcher::fetch`].
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Attempts after a failure before giving up, reset whenever bytes arrive.
+const MAX_RETRIES: u32 = 5;
+
+/// Wait before the first retry, doubled for each one after.
+const BACKOFF: Duration = Duration::from_millis(250);
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
 
 pub struct Fetcher {
     url: String,
-    reader: Box<dyn Read + Send + Sync + 'static>,
+    agent: ureq::Agent,
+    reader: Option<Box<dyn Read + Send + Sync + 'static>>,
     bytes: Vec<u8>,
+    /// Total size, unknown for chunked responses.
+    len: Option<usize>,
+    chunk: Vec<u8>,
+    retries: u32,
+    /// When to reconnect after the connection failed.
+    retry_at: Option<Instant>,
+    finished: bool,
+    error: Option<FetchError>,
 }
 
 impl Debug for Fetcher {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Fetcher")
             .field("url", &self.url)
-            .field("bytes", &self.bytes.capacity())
+            .field("bytes", &self.bytes.len())
+            .field("len", &self.len)
+            .field("retries", &self.retries)
+            .field("retry_at", &self.retry_at)
             .finish()
     }
 }
 
 impl Fetcher {
-    pub fn new(url: String) -> Result<Self> {
-        let response = ureq::get(&url).call()?;
-        let len = if let Some(len) = response.header("Content-Length") {
-            len.parse()?
-        } else {
-            return Err(anyhow::anyhow!("No content length."));
-        };
+    /// Start fetching `url`, a request that fails in a way that may pass is retried by
+    /// [`Fetcher::fetch`].
+    ///
+    /// # Errors
+    ///
+    /// Will return [`FetchError`] if `url` can't be resolved or the server refuses the request.
+    pub fn new(url: String) -> Result<Self, FetchError> {
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(CONNECT_TIMEOUT)
+            .timeout_read(READ_TIMEOUT)
+            .build();
 
-        Ok(Self {
+        let mut fetcher = Self {
             url,
-            reader: response.into_reader(),
-            bytes: Vec::with_capacity(len),
-        })
+            agent,
+            reader: None,
+            bytes: Vec::new(),
+            len: None,
+            chunk: vec![0; CHUNK_SIZE],
+            retries: 0,
+            retry_at: None,
+            finished: false,
+            error: None,
+        };
+
+        match fetcher.connect() {
+            Ok(()) => Ok(fetcher),
+            Err(error) if error.is_transient() => {
+                fetcher.recover(error);
+                Ok(fetcher)
+            }
+            Err(error) => Err(error),
+        }
     }
 
-    pub fn fetch(&mut self) -> Result<FetchProgress> {
-        let reader = self.reader.as_mut();
-        reader.take(1000).read_to_end(&mut self.bytes)?;
+    /// Read the next chunk of the response.
+    ///
+    /// A dropped connection is resumed where it left off, after waiting a little longer each
+    /// time it happens in a row. Nothing is read while waiting, see [`Fetcher::retry_in`].
+    ///
+    /// # Errors
+    ///
+    /// Will return [`FetchError`] once resuming stops working or the fetch was cancelled,
+    /// and keep returning it after.
+    pub fn fetch(&mut self) -> Result<FetchProgress, FetchError> {
+        if let Some(error) = &self.error {
+            return Err(error.clone());
+        }
+        if self.finished {
+            return Ok(FetchProgress::Finished);
+        }
+
+        if let Some(retry_at) = self.retry_at {
+            if Instant::now() < retry_at {
+                return self.progress();
+            }
+            self.retry_at = None;
+            if let Err(error) = self.connect() {
+                self.recover(error);
+                return self.progress();
+            }
+        }
 
-        if self.bytes.len() >= self.bytes.capacity() {
+        let read = match self.reader.as_mut() {
+            Some(reader) => reader.read(&mut self.chunk),
+            None => Err(ErrorKind::NotConnected.into()),
+        };
+
+        match read {
+            Ok(0) => match self.len {
+                Some(len) if self.bytes.len() < len => {
+                    let message = format!(
+                        "Connection closed after {} of {len} bytes.",
+                        self.bytes.len()
+                    );
+                    self.recover(self.network_error(message));
+                }
+                _ => self.finished = true,
+            },
+            Ok(read) => {
+                self.bytes.extend_from_slice(&self.chunk[..read]);
+                self.retries = 0;
+                if matches!(self.len, Some(len) if self.bytes.len() >= len) {
+                    self.finished = true;
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => self.recover(self.network_error(e.to_string())),
+        }
+
+        self.progress()
+    }
+
+    /// How long until the next attempt to reconnect, `None` when not waiting to.
+    #[must_use]
+    pub fn retry_in(&self) -> Option<Duration> {
+        self.retry_at
+            .map(|retry_at| retry_at.saturating_duration_since(Instant::now()))
+    }
+
+    fn progress(&self) -> Result<FetchProgress, FetchError> {
+        if let Some(error) = &self.error {
+            Err(error.clone())
+        } else if self.finished {
             Ok(FetchProgress::Finished)
-        } else {
+        } else if let Some(len) = self.len {
             Ok(FetchProgress::Fetching(
-                (self.bytes.len() as f32 / self.bytes.capacity() as f32) * 100.,
+                (self.bytes.len() as f32 / len as f32) * 100.,
             ))
+        } else {
+            Ok(FetchProgress::FetchingBytes(self.bytes.len()))
         }
     }
 
+    /// Stop fetching, every call to [`Fetcher::fetch`] after returns [`FetchError::Cancelled`].
+    pub fn cancel(&mut self) {
+        self.reader = None;
+        self.retry_at = None;
+        self.error = Some(FetchError::Cancelled {
+            url: self.url.clone(),
+        });
+    }
+
     pub fn consume(self) -> Vec<u8> {
         self.bytes
     }
+
+    /// Request the rest of the response, everything when nothing has arrived yet.
+    fn connect(&mut self) -> Result<(), FetchError> {
+        let mut request = self.agent.get(&self.url);
+        if !self.bytes.is_empty() {
+            request = request.set("Range", &format!("bytes={}-", self.bytes.len()));
+        }
+
+        let response = request.call().map_err(|error| match error {
+            ureq::Error::Status(code, _) => FetchError::Status {
+                url: self.url.clone(),
+                code,
+            },
+            ureq::Error::Transport(transport) => match transport.kind() {
+                ureq::ErrorKind::InvalidUrl
+                | ureq::ErrorKind::UnknownScheme
+                | ureq::ErrorKind::Dns
+                | ureq::ErrorKind::InvalidProxyUrl => FetchError::Address {
+                    url: self.url.clone(),
+                    message: transport.to_string(),
+                },
+                _ => self.network_error(transport.to_string()),
+            },
+        })?;
+
+        // Servers that ignore the range send everything again.
+        if response.status() != 206 {
+            self.bytes.clear();
+            self.len = response
+                .header("Content-Length")
+                .and_then(|len| len.parse().ok());
+            if let Some(len) = self.len {
+                self.bytes.reserve(len);
+            }
+        }
+
+        self.reader = Some(response.into_reader());
+        Ok(())
+    }
+
+    fn network_error(&self, message: String) -> FetchError {
+        FetchError::Network {
+            url: self.url.clone(),
+            message,
+        }
+    }
+
+    /// Schedule a reconnect after `error` with backoff, or give up with it.
+    fn recover(&mut self, error: FetchError) {
+        self.reader = None;
+        if self.retries >= MAX_RETRIES || !error.is_transient() {
+            self.error = Some(error);
+            return;
+        }
+
+        let backoff = BACKOFF * 2_u32.pow(self.retries);
+        log::warn!("Retrying in {backoff:?} after: {error}");
+        self.retry_at = Some(Instant::now() + backoff);
+        self.retries += 1;
+    }
+}
+
+/// Fetch all of `url` before returning.
+///
+/// # Errors
+///
+/// Will return [`FetchError`] if the request fails.
+pub fn fetch_all(url: String) -> Result<Vec<u8>, FetchError> {
+    let mut fetcher = Fetcher::new(url)?;
+    loop {
+        if let Some(wait) = fetcher.retry_in() {
+            thread::sleep(wait);
+        }
+        match fetcher.fetch()? {
+            FetchProgress::Fetching(percent) => log::debug!("{percent:.0}% fetched"),
+            FetchProgress::FetchingBytes(bytes) => log::debug!("{bytes} bytes fetched"),
+            FetchProgress::Finished => return Ok(fetcher.consume()),
+        }
+    }
+}
+
+/// Like [`fetch_all`], but returns what `cache` holds for `key` instead when it can,
+/// and stores what it fetches there.
+///
+/// # Errors
+///
+/// Will return [`FetchError`] if `key` isn't cached and the request fails.
+pub fn fetch_cached(cache: &Cache, key: &str, url: String) -> Result<Vec<u8>, FetchError> {
+    if let Some(data) = cache.get(key) {
+        log::debug!("{key} loaded from the cache");
+        return Ok(data);
+    }
+
+    let data = fetch_all(url)?;
+    // A full disk only costs fetching again next time.
+    if let Err(e) = cache.put(key, &data) {
+        log::warn!("Could not cache {key}: {e}");
+    }
+    Ok(data)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::fetch::PlaylistPayload;
+    use crate::{fetch::PlaylistPayload, stand_in::StandIn};
 
-    #[test]
-    fn test_fetch() {
-        let url = format!(
-            "https://www.flashflashrevolution.com/game/r3/r3-songLoad.php?id={}&type=ChartFFR_music",
-            "8e058c682f875b36fac6fa065c33fd88"
-        );
-
-        let mut fetcher = Fetcher::new(url);
-
-        assert!(fetcher.is_ok(), "{:?}", fetcher.err());
-
-        if let Ok(fetcher) = fetcher.as_mut() {
-            loop {
-                let progress = fetcher.fetch();
-                if let Ok(progress) = progress {
-                    match progress {
-                        FetchProgress::Fetching(percent) => println!("%{:?} complete", percent),
-                        FetchProgress::Finished => break,
-                        FetchProgress::Error(_) => todo!(),
-                    }
-                }
+    fn fetch_to_end(fetcher: &mut Fetcher) -> Result<Vec<FetchProgress>, FetchError> {
+        let mut progress = Vec::new();
+        loop {
+            if let Some(wait) = fetcher.retry_in() {
+                thread::sleep(wait);
+            }
+            match fetcher.fetch()? {
+                FetchProgress::Finished => return Ok(progress),
+                fetching => progress.push(fetching),
             }
         }
     }
 
     #[test]
-    fn test_fetch_stream() {
-        let mut fetcher = Fetcher::new(
-            "https://www.flashflashrevolution.com/game/r3/r3-playlist.v2.php".to_string(),
-        );
-
-        assert!(fetcher.is_ok(), "{:?}", fetcher.err());
-
-        if let Ok(fetcher) = fetcher.as_mut() {
-            loop {
-                let progress = fetcher.fetch();
-                if let Ok(progress) = progress {
-                    match progress {
-                        FetchProgress::Fetching(percent) => println!("%{:?} complete", percent),
-                        FetchProgress::Finished => break,
-                        FetchProgress::Error(_) => todo!(),
-                    }
-                }
-            }
-        }
+    fn test_chunked() -> Result<(), FetchError> {
+        let stand_in = StandIn::script(&[
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n\
+              5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n",
+        ]);
 
-        if let Ok(fetcher) = fetcher {
-            let data = fetcher.consume();
-            let _playlist: PlaylistPayload = serde_json::from_slice(&data).unwrap();
-        }
+        let mut fetcher = Fetcher::new(stand_in.base_url())?;
+        let progress = fetch_to_end(&mut fetcher)?;
+        assert!(progress
+            .iter()
+            .all(|progress| matches!(progress, FetchProgress::FetchingBytes(_))));
+        assert_eq!(fetcher.consume(), b"hello world");
+        assert_eq!(stand_in.requests().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_resume_after_dropped_connection() -> Result<(), FetchError> {
+        let stand_in = StandIn::script(&[
+            b"HTTP/1.1 200 OK\r\nContent-Length: 11\r\nConnection: close\r\n\r\nhello",
+            b"HTTP/1.1 206 Partial Content\r\nContent-Length: 6\r\n\
+              Content-Range: bytes 5-10/11\r\nConnection: close\r\n\r\n world",
+        ]);
+
+        let mut fetcher = Fetcher::new(stand_in.base_url())?;
+        fetch_to_end(&mut fetcher)?;
+        assert_eq!(fetcher.consume(), b"hello world");
+
+        let requests = stand_in.requests();
+        assert!(!requests[0].to_lowercase().contains("range:"));
+        assert!(requests[1].to_lowercase().contains("range: bytes=5-"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_retry_unavailable() -> Result<(), FetchError> {
+        let stand_in = StandIn::script(&[
+            b"HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello",
+        ]);
+
+        let mut fetcher = Fetcher::new(stand_in.base_url())?;
+        // Waiting to retry doesn't hold up the caller.
+        assert!(fetcher.retry_in().is_some());
+        assert!(matches!(fetcher.fetch()?, FetchProgress::FetchingBytes(0)));
+        assert_eq!(stand_in.requests().len(), 1);
+
+        thread::sleep(BACKOFF);
+        fetch_to_end(&mut fetcher)?;
+        assert_eq!(fetcher.retry_in(), None);
+        assert_eq!(fetcher.consume(), b"hello");
+        assert_eq!(stand_in.requests().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors() -> Result<(), FetchError> {
+        let stand_in = StandIn::serve(&[]);
+        assert!(matches!(
+            Fetcher::new(stand_in.base_url()),
+            Err(FetchError::Status { code: 404, .. })
+        ));
+        assert!(matches!(
+            Fetcher::new("not a url".to_string()),
+            Err(FetchError::Address { .. })
+        ));
+        assert!(matches!(
+            Fetcher::new("http://rrr.invalid/".to_string()),
+            Err(FetchError::Address { .. })
+        ));
+
+        let stand_in = StandIn::script(&[
+            b"HTTP/1.1 200 OK\r\nContent-Length: 11\r\nConnection: close\r\n\r\nhello",
+            b"HTTP/1.1 410 Gone\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        ]);
+        let mut fetcher = Fetcher::new(stand_in.base_url())?;
+        let error = fetch_to_end(&mut fetcher).err();
+        assert!(matches!(error, Some(FetchError::Status { code: 410, .. })));
+        assert_eq!(fetcher.fetch().err(), error);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cancel() -> Result<(), FetchError> {
+        let stand_in = StandIn::serve(&[("/", b"hello world")]);
+
+        let mut fetcher = Fetcher::new(stand_in.base_url())?;
+        fetcher.cancel();
+        assert!(matches!(fetcher.fetch(), Err(FetchError::Cancelled { .. })));
+        Ok(())
+    }
+
+    #[test]
+    fn test_fetch_stand_in() -> Result<(), FetchError> {
+        let stand_in = StandIn::serve(&[
+            (
+                "/r3-playlist.v2.php",
+                include_bytes!("../../../test_assets/playlist.json"),
+            ),
+            (
+                "/r3-songLoad.php?id=8e058c682f875b36fac6fa065c33fd88&type=ChartFFR_music",
+                b"FWS chart",
+            ),
+        ]);
+        let endpoints = stand_in.endpoints();
+
+        let chart = fetch_all(endpoints.chart_url("8e058c682f875b36fac6fa065c33fd88"))?;
+        assert_eq!(chart, b"FWS chart");
+
+        let playlist = fetch_all(endpoints.playlist_url())?;
+        let playlist: PlaylistPayload = serde_json::from_slice(&playlist).unwrap();
+        assert_eq!(playlist.songs.len(), 2);
+        Ok(())
     }
 }
@@ -1,5 +1,6 @@
 #![allow(dead_code)]
 
+use crate::FetchError;
 use futures::StreamExt;
 use js_sys::{Array, Uint8Array};
 use std::fmt::Debug;
@@ -28,49 +29,85 @@ impl Debug for Fetcher {
 #[wasm_bindgen]
 impl Fetcher {
     #[wasm_bindgen]
-    pub async fn new(url: String) -> Result<Fetcher, JsValue> {
-        let window = window().unwrap_throw();
+    pub async fn new(url: String) -> Result<Fetcher, FetchError> {
+        let network_error = |message: String| FetchError::Network {
+            url: url.clone(),
+            message,
+        };
+        let decode_error = |message: &str| FetchError::Decode {
+            url: url.clone(),
+            message: message.to_string(),
+        };
+
+        let window = window().ok_or_else(|| network_error("No window to fetch from.".into()))?;
         let resp_value = JsFuture::from(window.fetch_with_str(url.as_str()))
             .await
-            .map_err(|_| "fetch failed")?;
-        let resp: Response = resp_value.dyn_into().unwrap_throw();
-        let raw_body = resp.body().unwrap_throw();
-        let body = ReadableStream::from_raw(raw_body.dyn_into().unwrap_throw());
-        let stream = body.into_stream();
+            .map_err(|error| network_error(format!("{error:?}")))?;
+        let resp: Response = resp_value
+            .dyn_into()
+            .map_err(|_| decode_error("Not a response."))?;
+        if !resp.ok() {
+            return Err(FetchError::Status {
+                url,
+                code: resp.status(),
+            });
+        }
+
         let len = resp
             .headers()
             .get("Content-Length")
-            .unwrap()
-            .unwrap()
-            .parse::<usize>()
-            .unwrap();
+            .ok()
+            .flatten()
+            .and_then(|len| len.parse::<usize>().ok())
+            .ok_or_else(|| FetchError::MissingLength { url: url.clone() })?;
+        let raw_body = resp
+            .body()
+            .ok_or_else(|| decode_error("Response has no body."))?;
+        let body = ReadableStream::from_raw(
+            raw_body
+                .dyn_into()
+                .map_err(|_| decode_error("Body is not a stream."))?,
+        );
+        let stream = body.into_stream();
 
         Ok(Self { url, stream, len })
     }
 
     #[wasm_bindgen]
-    pub async fn fetch_js(self) -> Array {
-        let mut stream = self.stream;
-        let mut bytes: Vec<u8> = Vec::with_capacity(self.len);
-        while let Some(Ok(chunk)) = stream.next().await {
-            let buffer = Uint8Array::new(&chunk);
-            bytes.extend(buffer.to_vec());
-        }
-
-        bytes.into_iter().map(JsValue::from).collect()
+    pub async fn fetch_js(self) -> Result<Array, FetchError> {
+        let bytes = self.fetch().await?;
+        Ok(bytes.into_iter().map(JsValue::from).collect())
     }
 }
 
 impl Fetcher {
-    pub async fn fetch(self) -> Vec<u8> {
+    /// # Errors
+    ///
+    /// Will return [`FetchError`] if the stream fails before the whole response arrives.
+    pub async fn fetch(self) -> Result<Vec<u8>, FetchError> {
         let mut stream = self.stream;
         let mut bytes: Vec<u8> = Vec::with_capacity(self.len);
-        while let Some(Ok(chunk)) = stream.next().await {
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|error| FetchError::Network {
+                url: self.url.clone(),
+                message: format!("{error:?}"),
+            })?;
             let buffer = Uint8Array::new(&chunk);
             bytes.extend(buffer.to_vec());
         }
 
-        bytes
+        if bytes.len() < self.len {
+            return Err(FetchError::Network {
+                url: self.url,
+                message: format!(
+                    "Connection closed after {} of {} bytes.",
+                    bytes.len(),
+                    self.len
+                ),
+            });
+        }
+
+        Ok(bytes)
     }
 }
 
@@ -1,14 +1,20 @@
+mod error;
 mod imp;
 
-use rrr_playlist::{Song, Stat};
+pub use error::FetchError;
+
+use rrr_playlist::{FFRPlaylist, Song, Stat};
+use rrr_types::SongID;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum FetchProgress {
+    /// Percent of the response received.
     Fetching(f32),
+    /// Bytes received, for responses that don't say how big they are.
+    FetchingBytes(usize),
     Finished,
-    Error(String),
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
@@ -45,6 +51,36 @@ pub struct Chart {
     pub time: String,
 }
 
+impl ManifestPayload {
+    #[must_use]
+    pub fn artist(&self, id: i32) -> Option<&Artist> {
+        self.artists.iter().find(|artist| artist.id == id)
+    }
+
+    /// The chart of song `id`, which the manifest calls its level.
+    #[must_use]
+    pub fn chart(&self, id: &SongID) -> Option<&Chart> {
+        self.charts
+            .iter()
+            .find(|chart| chart.level == i32::from(id.0))
+    }
+
+    /// The artist of song `id`, for showing their name and release date next to it.
+    #[must_use]
+    pub fn artist_of(&self, id: &SongID) -> Option<&Artist> {
+        self.chart(id).and_then(|chart| self.artist(chart.artist))
+    }
+}
+
+impl From<PlaylistPayload> for FFRPlaylist {
+    fn from(payload: PlaylistPayload) -> Self {
+        FFRPlaylist {
+            songs: payload.songs,
+            stats: payload.stats,
+        }
+    }
+}
+
 #[cfg(target_arch = "wasm32")]
 pub mod platform {
     pub use super::imp::platform::*;
@@ -0,0 +1,101 @@
+//! Where FFR serves its playlist, manifest and charts.
+//!
+//! Every URL is built from [`Endpoints`], so a local mirror or a test server can stand in
+//! for FFR.
+
+use serde::{Deserialize, Serialize};
+
+/// FFR's own game server.
+pub const DEFAULT_BASE_URL: &str = "https://www.flashflashrevolution.com/game/r3/";
+
+/// Stands for the chart's `hash_load` in [`Endpoints::chart_path`].
+pub const HASH_PLACEHOLDER: &str = "{hash}";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Endpoints {
+    pub base_url: String,
+    /// The playlist of every song, as JSON.
+    pub playlist_path: String,
+    /// Every artist and chart, as JSON.
+    ///
+    /// Where FFR serves the manifest isn't known, so it's only fetched once an endpoints file
+    /// sets this.
+    pub manifest_path: Option<String>,
+    /// A swf chart, with [`HASH_PLACEHOLDER`] where its `hash_load` goes.
+    pub chart_path: String,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            base_url: DEFAULT_BASE_URL.to_string(),
+            playlist_path: "r3-playlist.v2.php".to_string(),
+            manifest_path: None,
+            chart_path: format!("r3-songLoad.php?id={HASH_PLACEHOLDER}&type=ChartFFR_music"),
+        }
+    }
+}
+
+impl Endpoints {
+    /// FFR's paths on another server.
+    #[must_use]
+    pub fn with_base_url(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.to_string(),
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub fn playlist_url(&self) -> String {
+        self.url(&self.playlist_path)
+    }
+
+    /// `None` until [`Endpoints::manifest_path`] is set.
+    #[must_use]
+    pub fn manifest_url(&self) -> Option<String> {
+        self.manifest_path.as_deref().map(|path| self.url(path))
+    }
+
+    /// The chart whose playlist entry has `hash_load`.
+    #[must_use]
+    pub fn chart_url(&self, hash_load: &str) -> String {
+        self.url(&self.chart_path.replace(HASH_PLACEHOLDER, hash_load))
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!(
+            "{}/{}",
+            self.base_url.trim_end_matches('/'),
+            path.trim_start_matches('/')
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_urls() {
+        assert_eq!(
+            Endpoints::default().chart_url("8e058c682f875b36fac6fa065c33fd88"),
+            "https://www.flashflashrevolution.com/game/r3/r3-songLoad.php?id=8e058c682f875b36fac6fa065c33fd88&type=ChartFFR_music"
+        );
+        assert_eq!(
+            Endpoints::with_base_url("http://127.0.0.1:8000").playlist_url(),
+            "http://127.0.0.1:8000/r3-playlist.v2.php"
+        );
+        assert_eq!(Endpoints::default().manifest_url(), None);
+
+        let mirror = Endpoints {
+            chart_path: "/charts/{hash}.swf".to_string(),
+            ..Endpoints::with_base_url("http://mirror.local/ffr/")
+        };
+        assert_eq!(
+            mirror.chart_url("a054ce01d88f3cba3bc98f70d71b3278"),
+            "http://mirror.local/ffr/charts/a054ce01d88f3cba3bc98f70d71b3278.swf"
+        );
+    }
+}
@@ -1,3 +1,10 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub mod client;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod download;
 mod fetch;
+pub mod ffr;
+#[cfg(any(test, feature = "stand-in"))]
+pub mod stand_in;
 
 pub use fetch::*;
@@ -0,0 +1,165 @@
+//! A local HTTP server standing in for FFR in tests, so they run offline and always see the
+//! same responses.
+//!
+//! Other crates get it from the `stand-in` feature, meant for their dev-dependencies.
+
+use crate::ffr::Endpoints;
+use std::{
+    collections::VecDeque,
+    io::{Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+const NOT_FOUND: &[u8] =
+    b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+
+/// Serves fixed responses until dropped.
+pub struct StandIn {
+    address: SocketAddr,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+enum Responses {
+    Routes(Vec<(String, &'static [u8])>),
+    Script(VecDeque<&'static [u8]>),
+}
+
+impl StandIn {
+    /// Serve each body under its target, such as `/r3-playlist.v2.php`.
+    ///
+    /// A target with a query only matches that query, one without matches any.
+    /// Everything else is a 404.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if no local port is free.
+    #[must_use]
+    pub fn serve(routes: &[(&str, &'static [u8])]) -> Self {
+        let routes = routes
+            .iter()
+            .map(|&(target, body)| (target.to_string(), body))
+            .collect();
+        Self::start(Responses::Routes(routes))
+    }
+
+    /// Answer each request with the next of `responses`, whatever its target, then 404.
+    ///
+    /// Responses are raw HTTP, head included, so they can misbehave in ways a real server
+    /// might, such as closing the connection early.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if no local port is free.
+    #[must_use]
+    pub fn script(responses: &[&'static [u8]]) -> Self {
+        Self::start(Responses::Script(responses.iter().copied().collect()))
+    }
+
+    fn start(mut responses: Responses) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handle = {
+            let stop = stop.clone();
+            let requests = requests.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if stop.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        respond(stream, &mut responses, &requests);
+                    }
+                }
+            })
+        };
+
+        Self {
+            address,
+            stop,
+            handle: Some(handle),
+            requests,
+        }
+    }
+
+    #[must_use]
+    pub fn base_url(&self) -> String {
+        format!("http://{}/", self.address)
+    }
+
+    /// FFR's paths on this server.
+    #[must_use]
+    pub fn endpoints(&self) -> Endpoints {
+        Endpoints::with_base_url(&self.base_url())
+    }
+
+    /// The head of every request answered so far, in order.
+    #[must_use]
+    pub fn requests(&self) -> Vec<String> {
+        self.requests
+            .lock()
+            .map(|requests| requests.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for StandIn {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // Wake the server up so it notices.
+        let _ = TcpStream::connect(self.address);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn respond(mut stream: TcpStream, responses: &mut Responses, requests: &Mutex<Vec<String>>) {
+    let mut request = Vec::new();
+    let mut byte = [0];
+    while !request.ends_with(b"\r\n\r\n") && matches!(stream.read(&mut byte), Ok(1)) {
+        request.push(byte[0]);
+    }
+    let request = String::from_utf8_lossy(&request).into_owned();
+    let target = request.split(' ').nth(1).unwrap_or_default().to_string();
+    if let Ok(mut requests) = requests.lock() {
+        requests.push(request);
+    }
+
+    match responses {
+        Responses::Routes(routes) => {
+            let path = target.split('?').next().unwrap_or_default();
+            let body = routes
+                .iter()
+                .find(|(route, _)| *route == target)
+                .or_else(|| routes.iter().find(|(route, _)| route == path))
+                .map(|&(_, body)| body);
+
+            match body {
+                Some(body) => {
+                    let head = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        body.len()
+                    );
+                    let _ = stream.write_all(head.as_bytes());
+                    let _ = stream.write_all(body);
+                }
+                None => {
+                    let _ = stream.write_all(NOT_FOUND);
+                }
+            }
+        }
+        Responses::Script(script) => {
+            let _ = stream.write_all(script.pop_front().unwrap_or(NOT_FOUND));
+        }
+    }
+}
@@ -71,12 +71,10 @@ impl<S: Mode, T: TimeTrait> RustRustRevolution<S, T> {
 
         for action in self.actions.drain(..) {
             let core_settings = self.state.core_settings();
-            if let Some(direction) = core_settings.key_to_direction_map.get(&action.key) {
-                self.play_state.do_action(
-                    direction,
-                    action.ts,
-                    self.state.core_settings().judge_offset,
-                );
+            let lanes = self.play_state.lane_count();
+            if let Some(lane) = core_settings.lane_for_key(lanes, &action.key) {
+                self.play_state
+                    .do_action(lane, action.ts, self.state.core_settings().judge_offset);
             }
         }
 
@@ -126,13 +124,16 @@ impl RustRustRevolution<Rendered, Time> {
             let temp_view = view.clone();
 
             // Get the view from the play state.
-            // Filter judged notes from the view.
-            let filtered_view =
-                temp_view.filter(|(_, note)| !self.play_state.judgements().contains_key(note));
+            // Filter judged notes and mines that were hit from the view.
+            let filtered_view = temp_view.filter(|(_, note)| {
+                !self.play_state.judgements().contains_key(note)
+                    && !self.play_state.mines_hit().contains(note)
+            });
 
             // Fill all of the necessary bits!
             renderer.render_field(
                 filtered_view,
+                self.play_state.lane_count(),
                 chart_progress,
                 start_position,
                 receptor_position.0 as i32,
@@ -18,6 +18,32 @@ pub fn blit<'a, S>(
     drawable: &S,
 ) where
     S: Drawable<'a>,
+{
+    blit_tinted(
+        screen,
+        screen_width,
+        screen_height,
+        dest_x,
+        dest_y,
+        dir,
+        drawable,
+        [255; 4],
+    );
+}
+
+/// Like [`blit`], with each channel of the drawable scaled by `tint`, RGBA.
+#[allow(clippy::too_many_arguments)]
+pub fn blit_tinted<'a, S>(
+    screen: &mut [u8],
+    screen_width: u32,
+    screen_height: u32,
+    dest_x: f32,
+    dest_y: f32,
+    dir: &Direction,
+    drawable: &S,
+    tint: [u8; 4],
+) where
+    S: Drawable<'a>,
 {
     let pixels = drawable.pixels();
     let width: f32 = drawable.width() as f32;
@@ -62,7 +88,10 @@ pub fn blit<'a, S>(
             source_x = source_x.clamp(0, drawable.width() as u32 - 1);
             source_y = source_y.clamp(0, drawable.height() as u32 - 1);
 
-            let source_pixel = pixels.get_pixel(source_x, source_y);
+            let mut source_pixel = pixels.get_pixel(source_x, source_y);
+            for (channel, tint) in source_pixel.0.iter_mut().zip(tint) {
+                *channel = (u16::from(*channel) * u16::from(tint) / 255) as u8;
+            }
 
             if source_pixel[3] != 0 {
                 screen[i + 3] = 255
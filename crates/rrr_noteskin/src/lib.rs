@@ -22,6 +22,21 @@ pub struct Noteskin {
     pub rows: usize,
 }
 
+/// Where each color sits in a noteskin image laid out like the default one, column by column.
+const DEFAULT_COLOR_INDICES: [NoteColor; 10] = [
+    NoteColor::Blue,
+    NoteColor::Orange,
+    NoteColor::Red,
+    NoteColor::Cyan,
+    NoteColor::Pink,
+    NoteColor::White,
+    NoteColor::Green,
+    NoteColor::Purple,
+    NoteColor::Yellow,
+    NoteColor::Receptor,
+];
+const DEFAULT_ROWS: usize = 3;
+
 impl Default for Noteskin {
     fn default() -> Self {
         let noteskin_bytes = DEFAULT_NOTESKIN;
@@ -30,22 +45,10 @@ impl Default for Noteskin {
         Self {
             note_width: 64,
             note_height: 64,
-            color_indices: [
-                NoteColor::Blue,
-                NoteColor::Orange,
-                NoteColor::Red,
-                NoteColor::Cyan,
-                NoteColor::Pink,
-                NoteColor::White,
-                NoteColor::Green,
-                NoteColor::Purple,
-                NoteColor::Yellow,
-                NoteColor::Receptor,
-            ]
-            .to_vec(),
+            color_indices: DEFAULT_COLOR_INDICES.to_vec(),
             rotations: [0, 90, 180, 270].to_vec(),
             image: noteskin_image,
-            rows: 3,
+            rows: DEFAULT_ROWS,
         }
     }
 }
@@ -90,6 +93,38 @@ impl Noteskin {
         }
     }
 
+    /// Load a noteskin image laid out like the default one, with square notes `note_size` wide.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `image_bytes` is not an image or is too small to hold every color.
+    pub fn from_image(image_bytes: &[u8], note_size: usize) -> anyhow::Result<Self> {
+        let image = image::load_from_memory(image_bytes)?;
+
+        let columns = (DEFAULT_COLOR_INDICES.len() + DEFAULT_ROWS - 1) / DEFAULT_ROWS;
+        let needed = (note_size * columns, note_size * DEFAULT_ROWS);
+        let (width, height) = image.dimensions();
+        if note_size == 0
+            || usize::try_from(width)? < needed.0
+            || usize::try_from(height)? < needed.1
+        {
+            return Err(anyhow::anyhow!(
+                "Noteskin image is {width}x{height}, {note_size}px notes need at least {}x{}.",
+                needed.0,
+                needed.1
+            ));
+        }
+
+        Ok(Self::new(
+            note_size,
+            note_size,
+            DEFAULT_COLOR_INDICES.to_vec(),
+            [0, 90, 180, 270].to_vec(),
+            image,
+            DEFAULT_ROWS,
+        ))
+    }
+
     pub fn get_rotations(&self) -> &[usize] {
         &self.rotations
     }
@@ -177,4 +212,13 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_from_image() -> anyhow::Result<()> {
+        let noteskin = Noteskin::from_image(DEFAULT_NOTESKIN, 64)?;
+        assert_eq!(noteskin.note_width, 64);
+        assert!(Noteskin::from_image(DEFAULT_NOTESKIN, 128).is_err());
+        assert!(Noteskin::from_image(b"not an image", 64).is_err());
+        Ok(())
+    }
 }
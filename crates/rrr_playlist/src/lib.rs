@@ -1,14 +1,28 @@
-// How is construction and ownership going to happen here.
-// Do I want to rely on rrr_fetch? Probably not.
+// Fetching is left to rrr_fetch, this crate only reads playlists it is given or finds on disk.
 
-// Load playlist.
-//  Cache Layer
-//  Fetch Layer
-//  Parse Layer (optional)
-// Construct playlist struct.
+pub mod query;
+pub mod user;
 
+use rrr_data::DataError;
+use rrr_types::SongID;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{collections::HashMap, path::PathBuf, time::Duration};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum PlaylistError {
+    #[error("Could not read or write {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid playlist: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Data(#[from] DataError),
+}
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Song {
@@ -30,6 +44,14 @@ pub struct Song {
     pub swf_end_delay: i64,
 }
 
+impl Song {
+    /// How long the song is, read from its `time`.
+    #[must_use]
+    pub fn length(&self) -> Option<Duration> {
+        query::parse_time(&self.time)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Stat {
     pub total_length: i64,
@@ -74,5 +96,102 @@ pub struct FFRPlaylist {
     pub stats: HashMap<String, Stat>,
 }
 
+impl FFRPlaylist {
+    /// Read the JSON FFR serves its playlist as.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`PlaylistError`] if the JSON isn't a playlist.
+    pub fn from_json(json: &[u8]) -> Result<Self, PlaylistError> {
+        Ok(serde_json::from_slice(json)?)
+    }
+
+    /// Read a playlist saved from FFR.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`PlaylistError`] if the file can't be read or isn't a playlist.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open(path: &std::path::Path) -> Result<Self, PlaylistError> {
+        Self::from_json(&read_file(path)?)
+    }
+
+    /// The song FFR knows by `id`, which it calls the song's level.
+    #[must_use]
+    pub fn song(&self, id: &SongID) -> Option<&Song> {
+        self.songs.iter().find(|song| song.level == i64::from(id.0))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_file(path: &std::path::Path) -> Result<Vec<u8>, PlaylistError> {
+    std::fs::read(path).map_err(|source| PlaylistError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
 pub trait Playlist {}
 impl Playlist for FFRPlaylist {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(crate) fn song(level: i64, hash_load: &str) -> Song {
+        Song {
+            level,
+            genre: 1,
+            name: format!("Song {level}"),
+            author: "Artist".to_string(),
+            author_url: String::new(),
+            stepauthor: "Stepper".to_string(),
+            difficulty: 10,
+            style: String::new(),
+            time: "1:30".to_string(),
+            order: level,
+            note_count: 100,
+            nps_min: 1,
+            nps_max: 5,
+            data_nps: String::new(),
+            hash_load: hash_load.to_string(),
+            swf_end_delay: 0,
+        }
+    }
+
+    #[test]
+    fn test_song_by_id() {
+        let playlist = FFRPlaylist {
+            songs: vec![song(1, "aaaa"), song(7, "bbbb")],
+            stats: HashMap::new(),
+        };
+
+        assert_eq!(
+            playlist
+                .song(&SongID(7))
+                .map(|song| song.hash_load.as_str()),
+            Some("bbbb")
+        );
+        assert_eq!(playlist.song(&SongID(2)), None);
+    }
+
+    #[test]
+    fn test_open() -> Result<(), PlaylistError> {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("playlist.json");
+        let playlist = FFRPlaylist {
+            songs: vec![song(1, "aaaa")],
+            stats: HashMap::new(),
+        };
+
+        std::fs::write(&path, serde_json::to_vec(&playlist)?).unwrap();
+        assert_eq!(FFRPlaylist::open(&path)?, playlist);
+
+        let _ = std::fs::remove_file(&path);
+        assert!(matches!(
+            FFRPlaylist::open(&path),
+            Err(PlaylistError::Io { .. })
+        ));
+        Ok(())
+    }
+}
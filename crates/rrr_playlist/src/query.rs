@@ -0,0 +1,286 @@
+//! Finding songs in a playlist, for song selection.
+
+use crate::{FFRPlaylist, Song};
+use std::{cmp::Ordering, ops::RangeInclusive, time::Duration};
+
+/// What to sort songs by. Ties keep their playlist order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// The order FFR lists songs in.
+    Order,
+    Name,
+    Author,
+    Genre,
+    Level,
+    Difficulty,
+    NoteCount,
+    /// The song's peak NPS.
+    Nps,
+    /// Songs whose length can't be read come last.
+    Length,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+/// Filters and sorting for the songs of an [`FFRPlaylist`], every filter left out matches
+/// every song.
+///
+/// ```
+/// # use rrr_playlist::query::{Query, SortKey, SortOrder};
+/// let query = Query::new()
+///     .with_text("cosmic")
+///     .with_difficulty(10..=40)
+///     .with_sort(SortKey::Nps, SortOrder::Descending);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    text: Option<String>,
+    genre: Option<i64>,
+    level: Option<RangeInclusive<i64>>,
+    difficulty: Option<RangeInclusive<i64>>,
+    note_count: Option<RangeInclusive<i64>>,
+    nps: Option<RangeInclusive<i64>>,
+    length: Option<RangeInclusive<Duration>>,
+    sort: Option<(SortKey, SortOrder)>,
+}
+
+impl Query {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Songs with `text` in their name or author, ignoring case.
+    #[must_use]
+    pub fn with_text(self, text: &str) -> Self {
+        Self {
+            text: Some(text.to_lowercase()),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn with_genre(self, genre: i64) -> Self {
+        Self {
+            genre: Some(genre),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn with_level(self, level: RangeInclusive<i64>) -> Self {
+        Self {
+            level: Some(level),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn with_difficulty(self, difficulty: RangeInclusive<i64>) -> Self {
+        Self {
+            difficulty: Some(difficulty),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn with_note_count(self, note_count: RangeInclusive<i64>) -> Self {
+        Self {
+            note_count: Some(note_count),
+            ..self
+        }
+    }
+
+    /// Songs whose peak NPS is in `nps`.
+    #[must_use]
+    pub fn with_nps(self, nps: RangeInclusive<i64>) -> Self {
+        Self {
+            nps: Some(nps),
+            ..self
+        }
+    }
+
+    /// Songs whose [`Song::length`] is in `length`, songs without one never match.
+    #[must_use]
+    pub fn with_length(self, length: RangeInclusive<Duration>) -> Self {
+        Self {
+            length: Some(length),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn with_sort(self, key: SortKey, order: SortOrder) -> Self {
+        Self {
+            sort: Some((key, order)),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn matches(&self, song: &Song) -> bool {
+        let within = |range: &Option<RangeInclusive<i64>>, value| {
+            range.as_ref().map_or(true, |range| range.contains(&value))
+        };
+
+        self.text.as_ref().map_or(true, |text| {
+            song.name.to_lowercase().contains(text) || song.author.to_lowercase().contains(text)
+        }) && self.genre.map_or(true, |genre| song.genre == genre)
+            && within(&self.level, song.level)
+            && within(&self.difficulty, song.difficulty)
+            && within(&self.note_count, song.note_count)
+            && within(&self.nps, song.nps_max)
+            && self.length.as_ref().map_or(true, |range| {
+                song.length()
+                    .map_or(false, |length| range.contains(&length))
+            })
+    }
+
+    /// The songs in `playlist` that match, sorted.
+    #[must_use]
+    pub fn run<'a>(&self, playlist: &'a FFRPlaylist) -> Vec<&'a Song> {
+        let mut songs: Vec<&Song> = playlist
+            .songs
+            .iter()
+            .filter(|song| self.matches(song))
+            .collect();
+
+        if let Some((key, order)) = self.sort {
+            songs.sort_by(|a, b| {
+                let ordering = compare(key, a, b);
+                match (key, a.length(), b.length()) {
+                    // Unreadable lengths stay last either way.
+                    (SortKey::Length, Some(_), None) | (SortKey::Length, None, Some(_)) => ordering,
+                    _ if order == SortOrder::Descending => ordering.reverse(),
+                    _ => ordering,
+                }
+            });
+        }
+
+        songs
+    }
+}
+
+fn compare(key: SortKey, a: &Song, b: &Song) -> Ordering {
+    match key {
+        SortKey::Order => a.order.cmp(&b.order),
+        SortKey::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+        SortKey::Author => a.author.to_lowercase().cmp(&b.author.to_lowercase()),
+        SortKey::Genre => a.genre.cmp(&b.genre),
+        SortKey::Level => a.level.cmp(&b.level),
+        SortKey::Difficulty => a.difficulty.cmp(&b.difficulty),
+        SortKey::NoteCount => a.note_count.cmp(&b.note_count),
+        SortKey::Nps => a.nps_max.cmp(&b.nps_max),
+        SortKey::Length => match (a.length(), b.length()) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        },
+    }
+}
+
+/// Reads FFR's `m:ss` or `h:mm:ss` song times.
+pub(crate) fn parse_time(time: &str) -> Option<Duration> {
+    let mut seconds: u64 = 0;
+    let mut parts = 0;
+    for part in time.trim().split(':') {
+        let value: u64 = part.parse().ok()?;
+        if parts > 0 && value >= 60 {
+            return None;
+        }
+        seconds = seconds.checked_mul(60)?.checked_add(value)?;
+        parts += 1;
+    }
+
+    (2..=3)
+        .contains(&parts)
+        .then(|| Duration::from_secs(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::song;
+
+    fn playlist() -> FFRPlaylist {
+        let songs = [
+            (1, "Exploration", "Cosmic Gate", 10, "1:30", 5),
+            (2, "Xelerate", "Ludicrous Speed", 45, "2:05", 12),
+            (3, "Cosmic Dust", "Someone", 30, "0:58", 9),
+            (4, "Broken", "Cosmic Gate", 60, "??", 20),
+        ];
+
+        FFRPlaylist {
+            songs: songs
+                .into_iter()
+                .map(|(level, name, author, difficulty, time, nps)| Song {
+                    name: name.to_string(),
+                    author: author.to_string(),
+                    difficulty,
+                    time: time.to_string(),
+                    nps_max: nps,
+                    genre: level % 2,
+                    ..song(level, "")
+                })
+                .collect(),
+            ..FFRPlaylist::default()
+        }
+    }
+
+    fn levels(songs: &[&Song]) -> Vec<i64> {
+        songs.iter().map(|song| song.level).collect()
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("1:30"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_time("0:05"), Some(Duration::from_secs(5)));
+        assert_eq!(parse_time("1:02:03"), Some(Duration::from_secs(3723)));
+        assert_eq!(parse_time("90"), None);
+        assert_eq!(parse_time("1:60"), None);
+        assert_eq!(parse_time(""), None);
+    }
+
+    #[test]
+    fn test_filters() {
+        let playlist = playlist();
+        let run = |query: Query| levels(&query.run(&playlist));
+
+        assert_eq!(run(Query::new()), [1, 2, 3, 4]);
+        assert_eq!(run(Query::new().with_text("COSMIC")), [1, 3, 4]);
+        assert_eq!(run(Query::new().with_genre(0)), [2, 4]);
+        assert_eq!(run(Query::new().with_level(2..=3)), [2, 3]);
+        assert_eq!(run(Query::new().with_difficulty(10..=30)), [1, 3]);
+        assert_eq!(run(Query::new().with_nps(9..=12)), [2, 3]);
+        assert_eq!(
+            run(Query::new().with_length(Duration::from_secs(60)..=Duration::from_secs(180))),
+            [1, 2]
+        );
+        assert_eq!(
+            run(Query::new().with_text("cosmic").with_difficulty(20..=100)),
+            [3, 4]
+        );
+        assert!(run(Query::new().with_note_count(0..=10)).is_empty());
+    }
+
+    #[test]
+    fn test_sort() {
+        let playlist = playlist();
+        let run = |key, order| levels(&Query::new().with_sort(key, order).run(&playlist));
+
+        assert_eq!(run(SortKey::Name, SortOrder::Ascending), [4, 3, 1, 2]);
+        assert_eq!(
+            run(SortKey::Difficulty, SortOrder::Descending),
+            [4, 2, 3, 1]
+        );
+        assert_eq!(run(SortKey::Author, SortOrder::Ascending), [1, 4, 2, 3]);
+        assert_eq!(run(SortKey::Length, SortOrder::Ascending), [3, 1, 2, 4]);
+        assert_eq!(run(SortKey::Length, SortOrder::Descending), [2, 1, 3, 4]);
+    }
+}
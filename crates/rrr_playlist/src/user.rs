@@ -0,0 +1,152 @@
+//! Playlists made by players, such as curated practice packs.
+//!
+//! They are kept through `rrr_data` and shared as JSON files such as
+//! `{"name": "Jumpstreams", "songs": [1, 7]}`, where each song is its FFR level ID.
+
+use crate::{FFRPlaylist, PlaylistError, Song};
+use rrr_types::SongID;
+use serde::{Deserialize, Serialize};
+
+/// Where the player's playlists are kept in `rrr_data`.
+const DATA_KEY: &str = "playlists";
+
+/// A named list of songs, in the order they are played.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserPlaylist {
+    pub name: String,
+    pub songs: Vec<SongID>,
+}
+
+impl UserPlaylist {
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            songs: Vec::new(),
+        }
+    }
+
+    /// The songs `playlist` knows, in this playlist's order.
+    #[must_use]
+    pub fn resolve<'a>(&self, playlist: &'a FFRPlaylist) -> Vec<&'a Song> {
+        self.songs
+            .iter()
+            .filter_map(|id| playlist.song(id))
+            .collect()
+    }
+
+    /// The songs `playlist` doesn't know, such as ones removed from FFR since.
+    #[must_use]
+    pub fn missing(&self, playlist: &FFRPlaylist) -> Vec<&SongID> {
+        self.songs
+            .iter()
+            .filter(|id| playlist.song(id).is_none())
+            .collect()
+    }
+
+    /// # Errors
+    ///
+    /// Will return [`PlaylistError`] if the JSON isn't a playlist.
+    pub fn from_json(json: &[u8]) -> Result<Self, PlaylistError> {
+        Ok(serde_json::from_slice(json)?)
+    }
+
+    /// # Errors
+    ///
+    /// Will return [`PlaylistError`] if the playlist can't be serialized.
+    pub fn to_json(&self) -> Result<Vec<u8>, PlaylistError> {
+        Ok(serde_json::to_vec_pretty(self)?)
+    }
+
+    /// Every playlist the player has kept.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`PlaylistError`] if they were kept but can't be read.
+    pub fn load_all() -> Result<Vec<Self>, PlaylistError> {
+        Ok(rrr_data::read(DATA_KEY)?.unwrap_or_default())
+    }
+
+    /// Keep `playlists` in place of every playlist kept before.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`PlaylistError`] if they can't be written.
+    pub fn save_all(playlists: &[Self]) -> Result<(), PlaylistError> {
+        Ok(rrr_data::write(DATA_KEY, &playlists)?)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl UserPlaylist {
+    /// Read a playlist file, such as one shared by another player.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`PlaylistError`] if the file can't be read or isn't a playlist.
+    pub fn open(path: &std::path::Path) -> Result<Self, PlaylistError> {
+        Self::from_json(&crate::read_file(path)?)
+    }
+
+    /// Write this playlist to a file to share it.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`PlaylistError`] if the file can't be written.
+    pub fn save(&self, path: &std::path::Path) -> Result<(), PlaylistError> {
+        std::fs::write(path, self.to_json()?).map_err(|source| PlaylistError::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::song;
+
+    #[test]
+    fn test_resolve() {
+        let playlist = FFRPlaylist {
+            songs: vec![song(1, "aaaa"), song(7, "bbbb")],
+            ..FFRPlaylist::default()
+        };
+        let user = UserPlaylist {
+            songs: vec![SongID(7), SongID(2), SongID(1)],
+            ..UserPlaylist::new("Practice")
+        };
+
+        let levels: Vec<i64> = user
+            .resolve(&playlist)
+            .iter()
+            .map(|song| song.level)
+            .collect();
+        assert_eq!(levels, [7, 1]);
+        assert_eq!(user.missing(&playlist), [&SongID(2)]);
+    }
+
+    #[test]
+    fn test_file() -> Result<(), PlaylistError> {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("playlist.json");
+        let user = UserPlaylist {
+            songs: vec![SongID(1), SongID(7)],
+            ..UserPlaylist::new("Jumpstreams")
+        };
+
+        user.save(&path)?;
+        assert_eq!(UserPlaylist::open(&path)?, user);
+        assert_eq!(
+            UserPlaylist::from_json(br#"{"name": "Jumpstreams", "songs": [1, 7]}"#)?,
+            user
+        );
+
+        std::fs::write(&path, "[1, 7]").unwrap();
+        assert!(matches!(
+            UserPlaylist::open(&path),
+            Err(PlaylistError::Json(_))
+        ));
+        Ok(())
+    }
+}
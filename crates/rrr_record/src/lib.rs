@@ -2,14 +2,26 @@ pub mod record;
 
 use anyhow::Result;
 use record::Record;
+#[cfg(feature = "bin")]
+use rrr_chart::native;
+#[cfg(feature = "osu")]
+use rrr_chart::osu;
 #[cfg(feature = "swf")]
-use rrr_chart::swf::{Compressed, SwfParser};
+use rrr_chart::swf::{Compressed, Parsing, SwfParser};
+#[cfg(any(feature = "json", feature = "ron"))]
+use rrr_chart::{ChartDocument, DocumentFormat};
 use std::fmt::Debug;
 
+#[cfg(feature = "swf")]
+pub use rrr_chart::swf::{ChartParseError, ParsingState};
+
 pub enum SourceType {
     SWF,
     BIN,
     SM,
+    OSU,
+    JSON,
+    RON,
 }
 
 #[cfg(feature = "swf")]
@@ -17,12 +29,33 @@ pub struct SwfChart {
     pub parser: SwfParser<Compressed>,
 }
 
+/// A decompressed swf that is parsed a little at a time, see [`Press<SwfPressing>::tick`].
+#[cfg(feature = "swf")]
+pub struct SwfPressing {
+    pub parser: SwfParser<Parsing>,
+}
+
+#[cfg(feature = "osu")]
+pub struct OsuChart {
+    pub osu: Vec<u8>,
+    pub audio: Vec<u8>,
+}
+
+#[cfg(any(feature = "json", feature = "ron"))]
+pub struct DocumentChart {
+    pub document: Vec<u8>,
+    pub format: DocumentFormat,
+    pub audio: Vec<u8>,
+}
+
 #[cfg(feature = "sm")]
 #[derive(Debug, Clone)]
 struct SmChart();
+/// A [native chart file](native), which carries its own audio.
 #[cfg(feature = "bin")]
-#[derive(Debug, Clone)]
-struct BinChart();
+pub struct BinChart {
+    pub bin: Vec<u8>,
+}
 
 pub struct Press<S: PressType> {
     pub s: S,
@@ -30,6 +63,12 @@ pub struct Press<S: PressType> {
 pub trait PressType {}
 #[cfg(feature = "swf")]
 impl PressType for SwfChart {}
+#[cfg(feature = "swf")]
+impl PressType for SwfPressing {}
+#[cfg(feature = "osu")]
+impl PressType for OsuChart {}
+#[cfg(any(feature = "json", feature = "ron"))]
+impl PressType for DocumentChart {}
 #[cfg(feature = "sm")]
 impl PressType for SmChart {}
 #[cfg(feature = "bin")]
@@ -48,31 +87,147 @@ impl RecordPressBuilder {
         }
     }
 
+    /// Pairs an osu!mania beatmap with the audio file named by its `AudioFilename`.
+    #[cfg(feature = "osu")]
+    #[must_use]
+    pub fn from_osu(osu: Vec<u8>, audio: Vec<u8>) -> Press<OsuChart> {
+        Press {
+            s: OsuChart { osu, audio },
+        }
+    }
+
+    /// Pairs a JSON [chart document](ChartDocument) with the audio file named by its `audio`.
+    #[cfg(feature = "json")]
+    #[must_use]
+    pub fn from_json(json: Vec<u8>, audio: Vec<u8>) -> Press<DocumentChart> {
+        Press {
+            s: DocumentChart {
+                document: json,
+                format: DocumentFormat::Json,
+                audio,
+            },
+        }
+    }
+
+    /// Pairs a RON [chart document](ChartDocument) with the audio file named by its `audio`.
+    #[cfg(feature = "ron")]
+    #[must_use]
+    pub fn from_ron(ron: Vec<u8>, audio: Vec<u8>) -> Press<DocumentChart> {
+        Press {
+            s: DocumentChart {
+                document: ron,
+                format: DocumentFormat::Ron,
+                audio,
+            },
+        }
+    }
+
     #[cfg(feature = "bin")]
     #[must_use]
-    pub fn from_bin() {}
+    pub fn from_bin(bin: Vec<u8>) -> Press<BinChart> {
+        Press {
+            s: BinChart { bin },
+        }
+    }
 
     #[cfg(feature = "sm")]
     #[must_use]
     pub fn from_sm() {}
 }
 
+#[cfg(feature = "swf")]
 impl Press<SwfChart> {
+    /// # Errors
+    ///
+    /// Will return [`ChartParseError`] describing where the swf is broken if it can't be read.
+    pub fn press(self) -> Result<Record, ChartParseError> {
+        let mut pressing = self.begin()?;
+        while let ParsingState::Parsing(_) = pressing.tick()? {}
+        pressing.finish()
+    }
+
+    /// Decompress the swf so it can be parsed over several frames.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ChartParseError::Decompression`] if the data is not an swf.
+    pub fn begin(self) -> Result<Press<SwfPressing>, ChartParseError> {
+        let parser = self.s.parser.decompress()?;
+
+        Ok(Press {
+            s: SwfPressing {
+                parser: parser.parse(),
+            },
+        })
+    }
+}
+
+#[cfg(feature = "swf")]
+impl Press<SwfPressing> {
+    /// Parse the next part of the swf, call once a frame so loading a large chart doesn't tank
+    /// frame performance.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ChartParseError`] if a tag is malformed.
+    pub fn tick(&mut self) -> Result<ParsingState, ChartParseError> {
+        self.s.parser.tick()
+    }
+
+    /// # Errors
+    ///
+    /// Will return [`ChartParseError::MissingChart`] if no usable chart was found.
+    pub fn finish(self) -> Result<Record, ChartParseError> {
+        let chart = self.s.parser.finish()?.consume();
+        let record = Record::new(chart.mp3, chart.chart)
+            .map_err(|_| ChartParseError::MissingChart)?
+            .with_metadata(chart.metadata);
+
+        Ok(match chart.audio {
+            Some(audio_info) => record.with_audio_info(audio_info),
+            None => record,
+        })
+    }
+}
+
+#[cfg(feature = "osu")]
+impl Press<OsuChart> {
     pub fn press(self) -> Result<Record> {
-        let parser_result = self.s.parser.decompress();
+        let osu = String::from_utf8_lossy(&self.s.osu);
+        let parsed = match osu::parse(&osu) {
+            Ok(parsed) => parsed,
+            Err(e) => return Err(anyhow::anyhow!("Beatmap Error: {}", e)),
+        };
+
+        Ok(Record::new(self.s.audio, parsed.chart)?.with_metadata(parsed.metadata))
+    }
+}
 
-        let parser = match parser_result {
-            Ok(parser) => parser,
-            Err(e) => return Err(anyhow::anyhow!("Decompression Error: {}", e)),
+#[cfg(any(feature = "json", feature = "ron"))]
+impl Press<DocumentChart> {
+    pub fn press(self) -> Result<Record> {
+        let document = String::from_utf8_lossy(&self.s.document);
+        let document = match ChartDocument::read(&document, self.s.format) {
+            Ok(document) => document,
+            Err(e) => return Err(anyhow::anyhow!("Document Error: {}", e)),
         };
 
-        let mut parsing = parser.parse();
+        Ok(Record::new(self.s.audio, document.chart())?.with_metadata(document.metadata))
+    }
+}
 
-        // Availalable so we don't tank frame performance.
-        let _state = parsing.tick();
-        let parsed = parsing.finish();
+#[cfg(feature = "bin")]
+impl Press<BinChart> {
+    pub fn press(self) -> Result<Record> {
+        let parsed = match native::parse(&self.s.bin) {
+            Ok(parsed) => parsed,
+            Err(e) => return Err(anyhow::anyhow!("Native Chart Error: {}", e)),
+        };
 
-        let chart = parsed.consume();
-        Record::new(chart.mp3, chart.chart)
+        let record = Record::new(parsed.audio, parsed.chart)?.with_metadata(parsed.metadata);
+        Ok(match parsed.audio_info {
+            Some(audio_info) => record.with_audio_info(audio_info),
+            None => record,
+        })
     }
 }
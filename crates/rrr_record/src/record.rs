@@ -1,12 +1,18 @@
 use btreemultimap::BTreeMultiMap;
-use rrr_chart::{RuntimeChart, RuntimeNote};
+use rrr_chart::{AudioInfo, ChartHash, Metadata, RuntimeChart, RuntimeNote};
+use rrr_playlist::Song;
 
 #[derive(Debug, Clone)]
 pub struct Record {
     pub optimized_chart: BTreeMultiMap<u32, RuntimeNote>,
     pub mp3: Vec<u8>,
     pub chart: RuntimeChart,
+    /// Identifies the notes of `chart`, see [`RuntimeChart::content_hash`].
+    pub hash: ChartHash,
     pub duration: u32,
+    pub metadata: Metadata,
+    /// Only known for formats that describe their audio, like swf.
+    pub audio_info: Option<AudioInfo>,
 }
 
 impl Record {
@@ -16,14 +22,47 @@ impl Record {
         if let Ok(duration) = chart.get_duration() {
             Ok(Self {
                 optimized_chart: create_optimized_chart(&chart),
+                hash: chart.content_hash(),
                 mp3,
                 chart,
                 duration,
+                metadata: Metadata::default(),
+                audio_info: None,
             })
         } else {
             Err(anyhow::anyhow!("Invalid chart of unknown length."))
         }
     }
+
+    #[must_use]
+    pub fn with_metadata(self, metadata: Metadata) -> Self {
+        Self { metadata, ..self }
+    }
+
+    /// Describe the chart the way FFR's playlist lists `song`, swf charts rarely say who the
+    /// artist is themselves.
+    #[must_use]
+    pub fn with_song(self, song: &Song) -> Self {
+        self.with_metadata(Metadata {
+            title: song.name.clone(),
+            artist: song.author.clone(),
+            author: song.stepauthor.clone(),
+        })
+    }
+
+    #[must_use]
+    pub fn with_audio_info(self, audio_info: AudioInfo) -> Self {
+        Self {
+            audio_info: Some(audio_info),
+            ..self
+        }
+    }
+
+    /// How late the audio plays compared to the chart, in milliseconds.
+    #[must_use]
+    pub fn sync_offset(&self) -> u32 {
+        self.audio_info.as_ref().map_or(0, AudioInfo::latency_ms)
+    }
 }
 
 fn create_optimized_chart(chart: &RuntimeChart) -> BTreeMultiMap<u32, RuntimeNote> {
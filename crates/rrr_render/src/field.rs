@@ -1,8 +1,13 @@
-use rrr_chart::{NoteColor, RuntimeNote};
+use rrr_chart::{NoteColor, NoteKind, RuntimeNote};
 use rrr_graphics::sprites;
 use rrr_math::lerp::Lerp;
 use rrr_noteskin::Noteskin;
-use rrr_types::Direction;
+use rrr_types::LaneCount;
+
+/// Mines are drawn as red receptors so they can't be mistaken for notes.
+const MINE_TINT: [u8; 4] = [255, 64, 64, 255];
+/// Fakes are faded, they're never judged.
+const FAKE_TINT: [u8; 4] = [255, 255, 255, 96];
 
 /// Standard time on screen is hard-coded to 1000 milliseconds.
 /// Use this function with a user set multiplier to get actual time on screen..
@@ -35,6 +40,7 @@ pub fn get_pos_from_ms(
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn draw_notes<'a>(
     view: impl IntoIterator<Item = (&'a u32, &'a RuntimeNote)>,
+    lanes: LaneCount,
     time_on_screen: u32,
     ms_chart_progress: u32,
     ms_note_render_offset: i32,
@@ -60,27 +66,28 @@ pub(crate) fn draw_notes<'a>(
 
         let lane_offset = noteskin.note_width.saturating_add(lane_gap as usize) as f32;
 
-        let lane_index = match note.direction {
-            Direction::Left => -1.5,
-            Direction::Down => -0.5,
-            Direction::Up => 0.5,
-            Direction::Right => 1.5,
-        };
-        let x = offset + (lane_offset * lane_index);
+        let x = offset + (lane_offset * lanes.center_offset(note.lane));
         let y = position as f32;
-        sprites::blit(
+        let (sprite, tint) = match note.kind {
+            NoteKind::Tap => (noteskin.get_note(note.color), [255; 4]),
+            NoteKind::Mine => (noteskin.get_note(NoteColor::Receptor), MINE_TINT),
+            NoteKind::Fake => (noteskin.get_note(note.color), FAKE_TINT),
+        };
+        sprites::blit_tinted(
             frame,
             screen_width,
             screen_height,
             x,
             y,
-            &note.direction,
-            &noteskin.get_note(note.color),
+            &note.lane.direction(lanes),
+            &sprite,
+            tint,
         );
     }
 }
 
 pub(crate) fn draw_receptors(
+    lanes: LaneCount,
     noteskin: &Noteskin,
     frame: &mut [u8],
     offset: f32,
@@ -91,42 +98,17 @@ pub(crate) fn draw_receptors(
 ) {
     let receptor_skin = noteskin.get_note(NoteColor::Receptor);
     let lane_offset = noteskin.note_width.saturating_add(gap as usize) as f32;
-    sprites::blit(
-        frame,
-        screen_width,
-        screen_height,
-        offset + (lane_offset * -1.5),
-        receptor_y,
-        &Direction::Left,
-        &receptor_skin,
-    );
-    sprites::blit(
-        frame,
-        screen_width,
-        screen_height,
-        offset + (lane_offset * -0.5),
-        receptor_y,
-        &Direction::Down,
-        &receptor_skin,
-    );
-    sprites::blit(
-        frame,
-        screen_width,
-        screen_height,
-        offset + (lane_offset * 0.5),
-        receptor_y,
-        &Direction::Up,
-        &receptor_skin,
-    );
-    sprites::blit(
-        frame,
-        screen_width,
-        screen_height,
-        offset + (lane_offset * 1.5),
-        receptor_y,
-        &Direction::Right,
-        &receptor_skin,
-    );
+    for lane in lanes.lanes() {
+        sprites::blit(
+            frame,
+            screen_width,
+            screen_height,
+            offset + (lane_offset * lanes.center_offset(lane)),
+            receptor_y,
+            &lane.direction(lanes),
+            &receptor_skin,
+        );
+    }
 }
 
 pub(crate) fn clear(frame: &mut [u8]) {
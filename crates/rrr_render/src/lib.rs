@@ -6,7 +6,7 @@ use pixels::{
 };
 use rrr_chart::RuntimeNote;
 use rrr_noteskin::Noteskin;
-use rrr_types::ReceptorPosition;
+use rrr_types::{LaneCount, ReceptorPosition};
 
 pub mod field;
 
@@ -21,6 +21,7 @@ impl Renderer {
     pub fn render_field<'a>(
         &mut self,
         view: impl IntoIterator<Item = (&'a u32, &'a RuntimeNote)>,
+        lanes: LaneCount,
         chart_progress: u32,
         start_position: i32,
         end_position: i32,
@@ -36,6 +37,7 @@ impl Renderer {
         let offset = self.width as f32 / 2.0 - noteskin.note_width as f32 * 0.5;
 
         field::draw_receptors(
+            lanes,
             noteskin,
             frame,
             offset,
@@ -49,6 +51,7 @@ impl Renderer {
 
         field::draw_notes(
             view,
+            lanes,
             time_on_screen,
             chart_progress,
             ms_offset,
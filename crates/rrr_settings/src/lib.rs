@@ -36,7 +36,7 @@ impl Default for Settings {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
 
     use rrr_types::ReceptorPosition;
 
@@ -53,12 +53,15 @@ mod tests {
         let settings = Settings {
             core: CoreSettings {
                 judge_offset: 100,
-                key_to_direction_map: HashMap::from([
-                    (KeyCode::Left, Direction::Left),
-                    (KeyCode::Down, Direction::Down),
-                    (KeyCode::Up, Direction::Up),
-                    (KeyCode::Right, Direction::Right),
-                ]),
+                key_maps: BTreeMap::from([(
+                    LaneCount::FOUR,
+                    HashMap::from([
+                        (KeyCode::Left, Lane(0)),
+                        (KeyCode::Down, Lane(1)),
+                        (KeyCode::Up, Lane(2)),
+                        (KeyCode::Right, Lane(3)),
+                    ]),
+                )]),
             },
             gap: 8,
             note_offset: -100,
@@ -1,29 +1,162 @@
 use rrr_input::KeyCode;
-use rrr_types::Direction;
+use rrr_types::{Lane, LaneCount};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 pub mod prelude {
     pub use rrr_input::KeyCode;
-    pub use rrr_types::Direction;
+    pub use rrr_types::{Direction, Lane, LaneCount};
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "CoreSettingsRepr")]
 pub struct CoreSettings {
     pub judge_offset: i8,
-    pub key_to_direction_map: HashMap<KeyCode, Direction>,
+    /// Which key plays which lane, for each lane count.
+    pub key_maps: BTreeMap<LaneCount, HashMap<KeyCode, Lane>>,
+}
+
+/// Settings as they're saved, older files only had `key_to_direction_map` for 4K charts.
+#[derive(Deserialize)]
+struct CoreSettingsRepr {
+    judge_offset: i8,
+    #[serde(default)]
+    key_maps: BTreeMap<LaneCount, HashMap<KeyCode, Lane>>,
+    key_to_direction_map: Option<HashMap<KeyCode, Lane>>,
+}
+
+impl From<CoreSettingsRepr> for CoreSettings {
+    fn from(repr: CoreSettingsRepr) -> Self {
+        let mut key_maps = repr.key_maps;
+        if let Some(key_map) = repr.key_to_direction_map {
+            key_maps.entry(LaneCount::FOUR).or_insert(key_map);
+        }
+
+        // Lane counts the file doesn't mention keep their default keys.
+        for (lanes, key_map) in CoreSettings::default().key_maps {
+            key_maps.entry(lanes).or_insert(key_map);
+        }
+
+        Self {
+            judge_offset: repr.judge_offset,
+            key_maps,
+        }
+    }
+}
+
+impl CoreSettings {
+    #[must_use]
+    pub fn lane_for_key(&self, lanes: LaneCount, key: &KeyCode) -> Option<Lane> {
+        self.key_maps.get(&lanes)?.get(key).copied()
+    }
 }
 
 impl Default for CoreSettings {
     fn default() -> Self {
+        let layouts: [&[KeyCode]; 5] = [
+            &[KeyCode::Left, KeyCode::Down, KeyCode::Up, KeyCode::Right],
+            &[
+                KeyCode::D,
+                KeyCode::F,
+                KeyCode::Space,
+                KeyCode::J,
+                KeyCode::K,
+            ],
+            &[
+                KeyCode::S,
+                KeyCode::D,
+                KeyCode::F,
+                KeyCode::J,
+                KeyCode::K,
+                KeyCode::L,
+            ],
+            &[
+                KeyCode::S,
+                KeyCode::D,
+                KeyCode::F,
+                KeyCode::Space,
+                KeyCode::J,
+                KeyCode::K,
+                KeyCode::L,
+            ],
+            &[
+                KeyCode::A,
+                KeyCode::S,
+                KeyCode::D,
+                KeyCode::F,
+                KeyCode::J,
+                KeyCode::K,
+                KeyCode::L,
+                KeyCode::Semicolon,
+            ],
+        ];
+
+        let key_maps = layouts
+            .into_iter()
+            .filter_map(|keys| {
+                let lanes = LaneCount::new(u8::try_from(keys.len()).ok()?)?;
+                let key_map = keys.iter().copied().zip(lanes.lanes()).collect();
+                Some((lanes, key_map))
+            })
+            .collect();
+
         Self {
             judge_offset: 0,
-            key_to_direction_map: HashMap::from([
-                (KeyCode::Left, Direction::Left),
-                (KeyCode::Down, Direction::Down),
-                (KeyCode::Up, Direction::Up),
-                (KeyCode::Right, Direction::Right),
-            ]),
+            key_maps,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_key_maps_cover_every_lane_count() {
+        let settings = CoreSettings::default();
+        for count in LaneCount::MIN..=LaneCount::MAX {
+            let lanes = LaneCount::new(count).unwrap();
+            let key_map = &settings.key_maps[&lanes];
+            assert_eq!(key_map.len(), usize::from(count));
+            assert!(lanes
+                .lanes()
+                .all(|lane| key_map.values().any(|l| *l == lane)));
         }
+
+        assert_eq!(
+            settings.lane_for_key(LaneCount::FOUR, &KeyCode::Up),
+            Some(Lane(2))
+        );
+    }
+
+    #[test]
+    fn read_old_key_to_direction_map() {
+        let json = r#"{"judge_offset":-2,"key_to_direction_map":{"A":"Left","S":"Down","W":"Up","D":"Right"}}"#;
+        let settings: CoreSettings = serde_json::from_str(json).unwrap();
+
+        assert_eq!(settings.judge_offset, -2);
+        assert_eq!(
+            settings.key_maps[&LaneCount::FOUR],
+            HashMap::from([
+                (KeyCode::A, Lane(0)),
+                (KeyCode::S, Lane(1)),
+                (KeyCode::W, Lane(2)),
+                (KeyCode::D, Lane(3)),
+            ])
+        );
+        assert_eq!(
+            settings.key_maps.len(),
+            CoreSettings::default().key_maps.len()
+        );
+    }
+
+    #[test]
+    fn round_trip_key_maps() {
+        let settings = CoreSettings::default();
+        let json = serde_json::to_string(&settings).unwrap();
+        assert_eq!(
+            serde_json::from_str::<CoreSettings>(&json).unwrap(),
+            settings
+        );
     }
 }
@@ -5,17 +5,29 @@ use crossterm::{
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use rrr_config::Config;
-use rrr_fetch::{platform::Fetcher, FetchProgress};
+use rrr_data::cache::Cache;
+use rrr_fetch::{
+    client,
+    ffr::Endpoints,
+    platform::{fetch_all, fetch_cached},
+    ManifestPayload,
+};
 use rrr_game::{
     builder::RustRustRevolutionBuilder,
     prelude::{Play, Turntable},
 };
+use rrr_playlist::{
+    query::{Query, SortKey, SortOrder},
+    user::UserPlaylist,
+    Song,
+};
 use rrr_record::RecordPressBuilder;
+use rrr_types::SongID;
 use rrr_window::{
     prelude::{EventLoopBuilder, EventLoopExtRunReturn},
     Window,
 };
-use std::sync::mpsc::{self, Sender};
+use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread;
 use std::{
     io,
@@ -26,7 +38,7 @@ use tui::{
     layout::{Alignment, Constraint, Direction, Layout},
     style::{Color, Modifier, Style},
     text::{Span, Spans},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
     Frame, Terminal,
 };
 
@@ -44,6 +56,9 @@ impl<T> StatefulList<T> {
     }
 
     fn next(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i >= self.items.len() - 1 {
@@ -58,6 +73,9 @@ impl<T> StatefulList<T> {
     }
 
     fn previous(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i == 0 {
@@ -78,26 +96,56 @@ impl<T> StatefulList<T> {
 ///
 /// Check the event handling at the bottom to see how to change the state on incoming events.
 /// Check the drawing logic for items on how to specify the highlighting style for selected items.
-struct App<'a> {
-    items: StatefulList<(&'a str, usize)>,
-    sender: Sender<u16>,
+struct App {
+    items: StatefulList<Song>,
+    /// Artists and release dates, the list still works without them.
+    manifest: Option<ManifestPayload>,
+    /// Sends the song to play.
+    sender: Sender<Song>,
+    status_receiver: Receiver<String>,
+    status: Option<String>,
 }
 
-impl<'a> App<'a> {
-    fn new(sender: Sender<u16>) -> App<'a> {
+impl App {
+    fn new(
+        songs: Vec<Song>,
+        manifest: Option<ManifestPayload>,
+        sender: Sender<Song>,
+        status_receiver: Receiver<String>,
+    ) -> App {
         App {
-            items: StatefulList::with_items(vec![("Song 1", 1), ("Song 2", 2)]),
+            items: StatefulList::with_items(songs),
+            manifest,
             sender,
+            status_receiver,
+            status: None,
         }
     }
 
-    /// Rotate through the event list.
-    /// This only exists to simulate some kind of "progress"
-    fn on_tick(&mut self) {}
+    /// Show the latest news from the player, such as why a song failed to load.
+    fn on_tick(&mut self) {
+        if let Some(status) = self.status_receiver.try_iter().last() {
+            self.status = Some(status);
+        }
+    }
 }
 
-pub fn init() -> Result<()> {
+/// Run the song picker, playing songs from `endpoints`.
+///
+/// Every song FFR has is listed unless a user `playlist` is given.
+pub fn init(endpoints: Endpoints, playlist: Option<UserPlaylist>) -> Result<()> {
     let (tx, rx) = mpsc::channel();
+    let (status_tx, status_rx) = mpsc::channel();
+
+    let cache = Cache::open().ok();
+    let songs = match load_songs(&endpoints, cache.as_ref(), playlist.as_ref()) {
+        Ok(songs) => songs,
+        Err(e) => {
+            let _res = status_tx.send(format!("Could not load the playlist: {e:#}"));
+            Vec::new()
+        }
+    };
+    let manifest = load_manifest(&endpoints, cache.as_ref());
 
     let terminal_join = thread::spawn(move || {
         // setup terminal
@@ -109,7 +157,7 @@ pub fn init() -> Result<()> {
 
         // create app and run it
         let tick_rate = Duration::from_millis(250);
-        let app = App::new(tx);
+        let app = App::new(songs, manifest, tx, status_rx);
         let res = run_app(&mut terminal, app, tick_rate);
 
         // restore terminal
@@ -128,41 +176,18 @@ pub fn init() -> Result<()> {
 
     let mut event_loop = EventLoopBuilder::new().build();
     loop {
-        if let Ok(_song_id) = rx.try_recv() {
+        if let Ok(song) = rx.try_recv() {
             {
-                let url = format!(
-                    "https://www.flashflashrevolution.com/game/r3/r3-songLoad.php?id={}&type=ChartFFR_music",
-                    "8e058c682f875b36fac6fa065c33fd88"
-                );
-
-                let mut fetcher = Fetcher::new(url);
-
-                assert!(fetcher.is_ok(), "{:?}", fetcher.err());
-
-                if let Ok(fetcher) = fetcher.as_mut() {
-                    loop {
-                        let progress = fetcher.fetch();
-                        if let Ok(progress) = progress {
-                            match progress {
-                                FetchProgress::Fetching(percent) => {
-                                    println!("%{:?} complete", percent)
-                                }
-                                FetchProgress::Finished => break,
-                                FetchProgress::Error(_) => todo!(),
-                            }
-                        }
-                    }
-                }
+                let url = endpoints.chart_url(&song.hash_load);
 
-                let data = if let Ok(fetcher) = fetcher {
-                    fetcher.consume()
-                } else {
-                    return Err(anyhow::anyhow!("Failed to fetch."));
+                let data = match load_chart(cache.as_ref(), &song.hash_load, url) {
+                    Ok(data) => data,
+                    Err(e) => {
+                        let _res = status_tx.send(format!("Could not load the song: {e:#}"));
+                        continue;
+                    }
                 };
 
-                let record_press = RecordPressBuilder::from_swf(data);
-                let record = record_press.press();
-
                 let config = Config::default();
                 let mut window = Window::new(config, &mut event_loop)?;
                 let renderer = futures::executor::block_on(async {
@@ -171,13 +196,23 @@ pub fn init() -> Result<()> {
                         .await
                 })?;
 
-                let turntable = Turntable::load(record.unwrap());
-                let play = Play::new(turntable);
+                let record = window
+                    .press(RecordPressBuilder::from_swf(data))
+                    .unwrap_or_else(|e| {
+                        let _res = status_tx.send(format!("Could not load the song: {e}"));
+                        None
+                    });
 
-                let mut rrr = RustRustRevolutionBuilder::with_play(play)
-                    .with_renderer(renderer)
-                    .build();
-                window.run_once(&mut rrr);
+                // Nothing to play if the window was closed while loading.
+                if let Some(record) = record {
+                    let turntable = Turntable::load(record.with_song(&song));
+                    let play = Play::new(turntable);
+
+                    let mut rrr = RustRustRevolutionBuilder::with_play(play)
+                        .with_renderer(renderer)
+                        .build();
+                    window.run_once(&mut rrr);
+                }
             }
 
             // Make sure window is dropped by running run_return again.
@@ -194,6 +229,66 @@ pub fn init() -> Result<()> {
     Ok(())
 }
 
+/// FFR's playlist is fetched again each time, the cached one is only used offline.
+fn load_songs(
+    endpoints: &Endpoints,
+    cache: Option<&Cache>,
+    playlist: Option<&UserPlaylist>,
+) -> Result<Vec<Song>> {
+    let ffr = match cache {
+        Some(cache) => client::fetch_and_cache_playlist(cache, endpoints)
+            .or_else(|e| client::cached_playlist(cache, endpoints).ok_or(e))?,
+        None => client::fetch_playlist(endpoints)?.into(),
+    };
+
+    let songs = match playlist {
+        Some(playlist) => playlist.resolve(&ffr),
+        None => Query::new()
+            .with_sort(SortKey::Order, SortOrder::Ascending)
+            .run(&ffr),
+    };
+    Ok(songs.into_iter().cloned().collect())
+}
+
+/// Like the playlist, the manifest is fetched again each time and the cached one only used
+/// offline. Nothing is requested unless the endpoints say where the manifest is.
+fn load_manifest(endpoints: &Endpoints, cache: Option<&Cache>) -> Option<ManifestPayload> {
+    match cache {
+        Some(cache) => client::fetch_and_cache_manifest(cache, endpoints)
+            .ok()
+            .flatten()
+            .or_else(|| client::cached_manifest(cache, endpoints)),
+        None => client::fetch_manifest(endpoints).ok().flatten(),
+    }
+}
+
+/// The line under a song's name: its artist, difficulty and length, and when it was released
+/// if the manifest knows.
+fn details(song: &Song, manifest: Option<&ManifestPayload>) -> String {
+    let id = u16::try_from(song.level).ok().map(SongID);
+    let artist = id.as_ref().and_then(|id| manifest?.artist_of(id));
+    let chart = id.as_ref().and_then(|id| manifest?.chart(id));
+
+    let mut details = format!(
+        "{} - difficulty {}, {}",
+        artist.map_or(song.author.as_str(), |artist| artist.name.as_str()),
+        song.difficulty,
+        song.time
+    );
+    if let Some(chart) = chart {
+        details.push_str(&format!(", released {}", chart.releasedate));
+    }
+    details
+}
+
+/// Charts are cached once played, so they can be played again offline.
+fn load_chart(cache: Option<&Cache>, hash_load: &str, url: String) -> Result<Vec<u8>> {
+    Ok(match cache {
+        Some(cache) => fetch_cached(cache, hash_load, url)?,
+        None => fetch_all(url)?,
+    })
+}
+
 fn run_app<B: Backend>(
     terminal: &mut Terminal<B>,
     mut app: App,
@@ -214,8 +309,10 @@ fn run_app<B: Backend>(
                     KeyCode::Down => app.items.next(),
                     KeyCode::Up => app.items.previous(),
                     KeyCode::Enter => {
-                        let song_id = app.items.state.selected().unwrap().try_into().unwrap();
-                        let _res = app.sender.send(song_id);
+                        let selected = app.items.state.selected();
+                        if let Some(song) = selected.and_then(|i| app.items.items.get(i)) {
+                            let _res = app.sender.send(song.clone());
+                        }
                     }
                     _ => {}
                 }
@@ -235,26 +332,26 @@ fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
         .split(f.size());
 
-    // Iterate through all elements in the `items` app and append some debug text to it.
+    // Each song's name, with its details under it.
     let items: Vec<ListItem> = app
         .items
         .items
         .iter()
-        .map(|i| {
-            let mut lines = vec![Spans::from(i.0)];
-            for _ in 0..i.1 {
-                lines.push(Spans::from(Span::styled(
-                    "Song Name",
+        .map(|song| {
+            let lines = vec![
+                Spans::from(song.name.as_str()),
+                Spans::from(Span::styled(
+                    details(song, app.manifest.as_ref()),
                     Style::default().add_modifier(Modifier::ITALIC),
-                )));
-            }
+                )),
+            ];
             ListItem::new(lines).style(Style::default().fg(Color::Black).bg(Color::White))
         })
         .collect();
 
     // Create a List from all list items and highlight the currently selected one
     let items = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title("List"))
+        .block(Block::default().borders(Borders::ALL).title("Songs"))
         .highlight_style(
             Style::default()
                 .bg(Color::LightGreen)
@@ -265,10 +362,84 @@ fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
     // We can now render the item list
     f.render_stateful_widget(items, chunks[0], &mut app.items.state);
 
-    let text = Spans::from("Select a song and press enter to play.");
-    let paragraph = Paragraph::new(text.clone())
+    let mut text = vec![Spans::from("Select a song and press enter to play.")];
+    if let Some(status) = &app.status {
+        text.push(Spans::from(""));
+        text.push(Spans::from(Span::styled(
+            status.as_str(),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    let paragraph = Paragraph::new(text)
         .style(Style::default())
         .block(Block::default().borders(Borders::ALL).title("Instruction"))
-        .alignment(Alignment::Left);
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: true });
     f.render_widget(paragraph, chunks[1]);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rrr_fetch::stand_in::StandIn;
+
+    const MANIFEST: &[u8] = include_bytes!("../../rrr_fetch/src/test_assets/manifest.json");
+    const PLAYLIST: &[u8] = include_bytes!("../../rrr_fetch/src/test_assets/playlist.json");
+
+    #[test]
+    fn test_load_songs() -> Result<()> {
+        let stand_in = StandIn::serve(&[("/r3-playlist.v2.php", PLAYLIST)]);
+        let endpoints = stand_in.endpoints();
+        let levels =
+            |songs: Vec<Song>| -> Vec<i64> { songs.iter().map(|song| song.level).collect() };
+
+        assert_eq!(levels(load_songs(&endpoints, None, None)?), [1, 7]);
+
+        let practice = UserPlaylist {
+            songs: vec![SongID(7), SongID(3)],
+            ..UserPlaylist::new("Practice")
+        };
+        assert_eq!(levels(load_songs(&endpoints, None, Some(&practice))?), [7]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_details() -> Result<()> {
+        let stand_in = StandIn::serve(&[
+            ("/r3-manifest.php", MANIFEST),
+            ("/r3-playlist.v2.php", PLAYLIST),
+        ]);
+        assert_eq!(load_manifest(&stand_in.endpoints(), None), None);
+        assert!(stand_in.requests().is_empty());
+
+        let endpoints = Endpoints {
+            manifest_path: Some("r3-manifest.php".to_string()),
+            ..stand_in.endpoints()
+        };
+        let manifest = load_manifest(&endpoints, None);
+        let song = Song {
+            author: "Unknown".to_string(),
+            ..load_songs(&endpoints, None, None)?.remove(0)
+        };
+
+        assert_eq!(
+            details(&song, manifest.as_ref()),
+            "Cosmic Gate - difficulty 24, 2:31, released 2007-10-02"
+        );
+
+        drop(stand_in);
+        assert_eq!(load_manifest(&endpoints, None), None);
+        assert_eq!(details(&song, None), "Unknown - difficulty 24, 2:31");
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_missing_chart() {
+        let stand_in = StandIn::serve(&[]);
+        let hash_load = "8e058c682f875b36fac6fa065c33fd88";
+        let url = stand_in.endpoints().chart_url(hash_load);
+
+        let error = load_chart(None, hash_load, url).unwrap_err();
+        assert!(error.to_string().contains("404"));
+    }
+}
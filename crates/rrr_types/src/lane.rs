@@ -0,0 +1,172 @@
+use crate::Direction;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// A column on the play field, counted from the left starting at 0.
+///
+/// Serialized as its index, but also reads the direction names 4K charts used to be written with.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+#[serde(from = "LaneRepr", into = "u8")]
+pub struct Lane(pub u8);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LaneRepr {
+    Index(u8),
+    Direction(Direction),
+}
+
+impl From<LaneRepr> for Lane {
+    fn from(repr: LaneRepr) -> Self {
+        match repr {
+            LaneRepr::Index(index) => Lane(index),
+            LaneRepr::Direction(direction) => Lane::from(direction),
+        }
+    }
+}
+
+impl From<Lane> for u8 {
+    fn from(lane: Lane) -> Self {
+        lane.0
+    }
+}
+
+impl From<Direction> for Lane {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::Left => Lane(0),
+            Direction::Down => Lane(1),
+            Direction::Up => Lane(2),
+            Direction::Right => Lane(3),
+        }
+    }
+}
+
+impl Display for Lane {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "lane {}", self.0)
+    }
+}
+
+impl Lane {
+    /// Which way the arrow in this lane points.
+    ///
+    /// Only 4K charts have arrows, every other lane count uses unrotated (down) notes.
+    #[must_use]
+    pub fn direction(self, lanes: LaneCount) -> Direction {
+        match (lanes.get(), self.0) {
+            (4, 0) => Direction::Left,
+            (4, 2) => Direction::Up,
+            (4, 3) => Direction::Right,
+            _ => Direction::Down,
+        }
+    }
+}
+
+/// How many lanes a chart is played on, between [`LaneCount::MIN`] and [`LaneCount::MAX`].
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub struct LaneCount(u8);
+
+impl LaneCount {
+    pub const MIN: u8 = 4;
+    pub const MAX: u8 = 8;
+    pub const FOUR: LaneCount = LaneCount(4);
+
+    #[must_use]
+    pub fn new(lanes: u8) -> Option<Self> {
+        (Self::MIN..=Self::MAX)
+            .contains(&lanes)
+            .then_some(Self(lanes))
+    }
+
+    #[must_use]
+    pub fn get(self) -> u8 {
+        self.0
+    }
+
+    #[must_use]
+    pub fn contains(self, lane: Lane) -> bool {
+        lane.0 < self.0
+    }
+
+    pub fn lanes(self) -> impl Iterator<Item = Lane> {
+        (0..self.0).map(Lane)
+    }
+
+    /// How far `lane` is from the middle of the field, in lane widths.
+    ///
+    /// For 4K this is -1.5, -0.5, 0.5 and 1.5.
+    #[must_use]
+    pub fn center_offset(self, lane: Lane) -> f32 {
+        f32::from(lane.0) - f32::from(self.0 - 1) / 2.
+    }
+}
+
+impl Default for LaneCount {
+    fn default() -> Self {
+        Self::FOUR
+    }
+}
+
+impl TryFrom<u8> for LaneCount {
+    type Error = String;
+
+    fn try_from(lanes: u8) -> Result<Self, Self::Error> {
+        Self::new(lanes).ok_or_else(|| {
+            format!(
+                "{lanes} lanes is not supported, expected {} to {}.",
+                Self::MIN,
+                Self::MAX
+            )
+        })
+    }
+}
+
+impl From<LaneCount> for u8 {
+    fn from(lanes: LaneCount) -> Self {
+        lanes.0
+    }
+}
+
+impl Display for LaneCount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}K", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lane_count_range() {
+        assert_eq!(LaneCount::new(3), None);
+        assert_eq!(LaneCount::new(8).map(LaneCount::get), Some(8));
+        assert_eq!(LaneCount::new(9), None);
+    }
+
+    #[test]
+    fn test_center_offset() {
+        let four: Vec<f32> = LaneCount::FOUR
+            .lanes()
+            .map(|lane| LaneCount::FOUR.center_offset(lane))
+            .collect();
+        assert_eq!(four, vec![-1.5, -0.5, 0.5, 1.5]);
+
+        let seven = LaneCount::new(7).unwrap();
+        assert_eq!(seven.center_offset(Lane(3)), 0.);
+    }
+
+    #[test]
+    fn test_direction_round_trip() {
+        for direction in [
+            Direction::Left,
+            Direction::Down,
+            Direction::Up,
+            Direction::Right,
+        ] {
+            assert_eq!(Lane::from(direction).direction(LaneCount::FOUR), direction);
+        }
+    }
+}
@@ -1,4 +1,6 @@
+mod lane;
 mod receptor_position;
+pub use lane::{Lane, LaneCount};
 pub use receptor_position::ReceptorPosition;
 
 use serde::{Deserialize, Serialize};
@@ -3,7 +3,7 @@
 use anyhow::{self, Result};
 use js_sys::{Function, Uint8Array};
 pub use rrr_fetch::platform::Fetcher;
-use rrr_fetch::{Chart, FetchProgress};
+use rrr_fetch::{ffr, Chart, FetchProgress};
 use rrr_game::{
     builder::BuildRendered,
     builder::RustRustRevolutionBuilder,
@@ -12,7 +12,7 @@ use rrr_game::{
     Rendered, RustRustRevolution,
 };
 use rrr_input::KeyCode;
-use rrr_record::{record::Record, RecordPressBuilder};
+use rrr_record::{record::Record, ParsingState, RecordPressBuilder};
 use rrr_render::{Renderer, RendererBuilder};
 use rrr_time::Time;
 use std::rc::Rc;
@@ -196,8 +196,14 @@ impl RRRBuilder {
         }
     }
 
+    /// Presses the swf a little each animation frame, calling `on_progress` with how far along
+    /// it is as a percentage.
     #[wasm_bindgen]
-    pub async fn build(self, raw_swf_data: Uint8Array) -> Result<RRR, JsValue> {
+    pub async fn build(
+        self,
+        raw_swf_data: Uint8Array,
+        on_progress: Option<Function>,
+    ) -> Result<RRR, JsValue> {
         let canv = self.canvas.clone().unwrap();
 
         let event_loop = EventLoop::new();
@@ -232,10 +238,19 @@ impl RRRBuilder {
 
         let data = raw_swf_data.to_vec();
 
-        let record_press = RecordPressBuilder::from_swf(data);
-        let record = record_press.press();
+        let chart_error = |e: rrr_record::ChartParseError| JsValue::from(e.to_string());
+        let mut pressing = RecordPressBuilder::from_swf(data)
+            .begin()
+            .map_err(chart_error)?;
+        while let ParsingState::Parsing(progress) = pressing.tick().map_err(chart_error)? {
+            if let Some(on_progress) = &on_progress {
+                on_progress.call1(&JsValue::null(), &JsValue::from(progress))?;
+            }
+            next_frame().await?;
+        }
+        let record = pressing.finish().map_err(chart_error)?;
 
-        let turntable = Turntable::load(record.unwrap());
+        let turntable = Turntable::load(record);
         let play = Play::new(turntable);
 
         let mut rrr_builder = RustRustRevolutionBuilder::with_play(play).with_renderer(renderer);
@@ -248,6 +263,50 @@ impl RRRBuilder {
     }
 }
 
+/// The server to fetch the playlist and charts from, FFR's own unless read from JSON.
+#[wasm_bindgen(js_name = Endpoints)]
+#[derive(Debug, Default)]
+pub struct WebEndpoints(ffr::Endpoints);
+
+#[wasm_bindgen(js_class = Endpoints)]
+impl WebEndpoints {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        WebEndpoints::default()
+    }
+
+    /// Reads [`ffr::Endpoints`] as JSON, fields left out keep FFR's.
+    #[wasm_bindgen]
+    pub fn from_json(json: &str) -> Result<WebEndpoints, JsValue> {
+        serde_json::from_str(json)
+            .map(WebEndpoints)
+            .map_err(|e| JsValue::from(format!("Invalid endpoints: {e}")))
+    }
+
+    #[wasm_bindgen]
+    pub fn playlist_url(&self) -> String {
+        self.0.playlist_url()
+    }
+
+    #[wasm_bindgen]
+    pub fn chart_url(&self, hash_load: &str) -> String {
+        self.0.chart_url(hash_load)
+    }
+}
+
+/// Resolves on the next animation frame, letting the page draw between chunks of work.
+async fn next_frame() -> Result<(), JsValue> {
+    let window = web_sys::window().ok_or("No window.")?;
+    let mut request = Ok(0);
+    let frame = js_sys::Promise::new(&mut |resolve, _reject| {
+        request = window.request_animation_frame(&resolve);
+    });
+    request?;
+
+    wasm_bindgen_futures::JsFuture::from(frame).await?;
+    Ok(())
+}
+
 #[wasm_bindgen]
 pub fn initialize() {
     std::panic::set_hook(Box::new(console_error_panic_hook::hook));
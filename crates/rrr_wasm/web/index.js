@@ -1,4 +1,4 @@
-import init, { initialize, RRRBuilder, Fetcher } from "./bin/rrr_wasm.js";
+import init, { initialize, RRRBuilder, Fetcher, Endpoints } from "./bin/rrr_wasm.js";
 
 var rrr = null;
 
@@ -19,20 +19,72 @@ async function main() {
         throw "Add `?song_id=id` to the end of the url.";
     }
 
-    const response = await (await fetch("https://www.flashflashrevolution.com/game/r3/r3-playlist.v2.php")).json();
+    var endpoints;
+    try {
+        endpoints = await load_endpoints();
+    } catch (error) {
+        show_error(error);
+        return;
+    }
+
+    const response = await (await fetch(endpoints.playlist_url())).json();
 
     console.log(response);
     const [song] = response.songs.filter(obj => {
         return obj.level == song_id;
     });
 
+    if (song === undefined) {
+        show_error(`Unknown song ID ${song_id}, it is not in the playlist.`);
+        return;
+    }
+
     // Possible fetch progress reference https://javascript.info/fetch-progress
-    var fetcher = await Fetcher.new(`https://www.flashflashrevolution.com/game/r3/r3-songLoad.php?id=${song.hash_load}&type=ChartFFR_music`);
-    var value = await fetcher.fetch_js();
-    rrr = await new RRRBuilder().with_canvas(canvas).build(value);
+    var value;
+    try {
+        var fetcher = await Fetcher.new(endpoints.chart_url(song.hash_load));
+        value = await fetcher.fetch_js();
+    } catch (error) {
+        show_error(`Could not load the song: ${error.message}`);
+        return;
+    }
+
+    let progress = document.body.appendChild(document.createElement("p"));
+    progress.setAttribute('class', 'progress');
+    try {
+        rrr = await new RRRBuilder().with_canvas(canvas).build(value, percent => {
+            progress.textContent = `Loading ${Math.round(percent)}%`;
+        });
+    } catch (error) {
+        show_error(`Could not load the song: ${error}`);
+        return;
+    } finally {
+        progress.remove();
+    }
     canvas.addEventListener("keyup", key_press);
 }
 
+// Serve an `endpoints.json` next to the page to play from a server laid out differently to FFR.
+async function load_endpoints() {
+    let response;
+    try {
+        response = await fetch("endpoints.json");
+    } catch (error) {
+        return new Endpoints();
+    }
+    if (!response.ok) {
+        return new Endpoints();
+    }
+    return Endpoints.from_json(await response.text());
+}
+
+function show_error(message) {
+    console.error(message);
+    let error = document.body.appendChild(document.createElement("p"));
+    error.setAttribute('class', 'error');
+    error.textContent = message;
+}
+
 function key_press(event) {
     console.log(event);
     if (event.code == "Space") {
@@ -2,6 +2,7 @@ use anyhow::{anyhow, Result};
 use rrr_config::Config;
 use rrr_game::{hit_action, Rendered, RustRustRevolution};
 use rrr_input::KeyCode;
+use rrr_record::{record::Record, ChartParseError, ParsingState, Press, SwfChart};
 use rrr_time::Time;
 use winit::{
     dpi::{PhysicalPosition, PhysicalSize},
@@ -18,6 +19,8 @@ pub mod prelude {
     };
 }
 
+const TITLE: &str = "Rust Rust Revolution";
+
 pub struct Window<'e> {
     event_loop: &'e mut EventLoop<()>,
     pub window: winit::window::Window,
@@ -27,7 +30,7 @@ impl<'e> Window<'e> {
     pub fn new(config: Config, event_loop: &'e mut EventLoop<()>) -> Result<Window> {
         let size = PhysicalSize::new(config.width, config.height);
         let window = match WindowBuilder::new()
-            .with_title("Rust Rust Revolution")
+            .with_title(TITLE)
             .with_inner_size(size)
             .with_resizable(false)
             .with_position(PhysicalPosition::new(config.window_x, config.window_y))
@@ -40,6 +43,49 @@ impl<'e> Window<'e> {
         Ok(Self { event_loop, window })
     }
 
+    /// Press a swf chart a little each frame, showing how far along it is in the title so
+    /// the window stays responsive while it loads.
+    ///
+    /// Returns `Ok(None)` if the window is closed before the chart is pressed.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ChartParseError`] if the swf can't be read.
+    pub fn press(&mut self, press: Press<SwfChart>) -> Result<Option<Record>, ChartParseError> {
+        let mut pressing = press.begin()?;
+        let mut state = Ok(ParsingState::Parsing(0.));
+
+        let window = &self.window;
+        self.event_loop.run_return(|event, _, control_flow| {
+            control_flow.set_poll();
+
+            match event {
+                winit::event::Event::MainEventsCleared => {
+                    state = pressing.tick();
+                    match &state {
+                        Ok(ParsingState::Parsing(progress)) => {
+                            window.set_title(&format!("{TITLE} - Loading {progress:.0}%"));
+                        }
+                        Ok(ParsingState::Finished) | Err(_) => control_flow.set_exit(),
+                    }
+                }
+
+                winit::event::Event::WindowEvent {
+                    event: winit::event::WindowEvent::CloseRequested,
+                    ..
+                } => control_flow.set_exit(),
+
+                _ => {}
+            }
+        });
+        self.window.set_title(TITLE);
+
+        match state? {
+            ParsingState::Finished => pressing.finish().map(Some),
+            ParsingState::Parsing(_) => Ok(None),
+        }
+    }
+
     pub fn run_once(&mut self, rrr: &mut RustRustRevolution<Rendered, Time>) {
         self.window.focus_window();
 
@@ -1,6 +1,6 @@
 use anyhow::Result;
 use rrr_config::Config;
-use rrr_fetch::{platform::Fetcher, FetchProgress};
+use rrr_fetch::{ffr::Endpoints, platform::fetch_all};
 use rrr_game::{
     builder::RustRustRevolutionBuilder,
     prelude::{rrr_render, Play, Turntable},
@@ -15,36 +15,9 @@ pub fn main() -> Result<()> {
         ));
     }
 
-    let url = format!(
-        "https://www.flashflashrevolution.com/game/r3/r3-songLoad.php?id={}&type=ChartFFR_music",
-        "a054ce01d88f3cba3bc98f70d71b3278"
-    );
+    let url = Endpoints::default().chart_url("a054ce01d88f3cba3bc98f70d71b3278");
 
-    let mut fetcher = Fetcher::new(url);
-
-    assert!(fetcher.is_ok(), "{:?}", fetcher.err());
-
-    if let Ok(fetcher) = fetcher.as_mut() {
-        loop {
-            let progress = fetcher.fetch();
-            if let Ok(progress) = progress {
-                match progress {
-                    FetchProgress::Fetching(percent) => println!("%{:?} complete", percent),
-                    FetchProgress::Finished => break,
-                    FetchProgress::Error(_) => todo!(),
-                }
-            }
-        }
-    }
-
-    let data = if let Ok(fetcher) = fetcher {
-        fetcher.consume()
-    } else {
-        return Err(anyhow::anyhow!("Failed to fetch."));
-    };
-
-    let record_press = RecordPressBuilder::from_swf(data);
-    let record = record_press.press();
+    let data = fetch_all(url)?;
 
     let config = Config::default();
     let mut event_loop = EventLoopBuilder::new().build();
@@ -53,7 +26,11 @@ pub fn main() -> Result<()> {
         rrr_render::RendererBuilder::new(config.width, config.height, &window.window).build(),
     )?;
 
-    let turntable = Turntable::load(record.unwrap());
+    let Some(record) = window.press(RecordPressBuilder::from_swf(data))? else {
+        return Ok(());
+    };
+
+    let turntable = Turntable::load(record);
     let play = Play::new(turntable);
 
     let mut rrr = RustRustRevolutionBuilder::with_play(play)
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rrr_record::RecordPressBuilder;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = RecordPressBuilder::from_swf(data.to_vec()).press();
+});
@@ -0,0 +1,23 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rrr_chart::swf::{ParsingState, SwfParser};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(parser) = SwfParser::new(data.to_vec()).decompress() else {
+        return;
+    };
+
+    // Tiny ticks so resuming the tag reader part way through the stream gets exercised too.
+    let mut parsing = parser.parse();
+    loop {
+        match parsing.tick_for(64) {
+            Ok(ParsingState::Parsing(_)) => {}
+            Ok(ParsingState::Finished) => {
+                let _ = parsing.finish();
+                break;
+            }
+            Err(_) => break,
+        }
+    }
+});
@@ -0,0 +1,108 @@
+use anyhow::{Context, Result};
+use rrr_chart::{osu, ChartDocument, DocumentFormat};
+use rrr_record::{record::Record, Press, RecordPressBuilder, SwfChart};
+use std::{fs, path::Path};
+
+/// A chart file from disk, swf charts are left unpressed so a window can press them a little
+/// each frame.
+pub enum ChartFile {
+    Swf(Press<SwfChart>),
+    Pressed {
+        record: Record,
+        /// The audio file next to the chart, for formats that keep their audio separate.
+        audio_filename: Option<String>,
+    },
+}
+
+/// Press a chart file from disk, see [`open`].
+pub fn read_record(path: &Path) -> Result<Record> {
+    match open(path)? {
+        ChartFile::Swf(press) => Ok(press.press()?),
+        ChartFile::Pressed { record, .. } => Ok(record),
+    }
+}
+
+/// Open a chart file from disk, picking the format from its extension.
+///
+/// Formats that keep their audio in a separate file load it from next to the chart,
+/// a missing audio file leaves the record silent.
+pub fn open(path: &Path) -> Result<ChartFile> {
+    let data = fs::read(path).with_context(|| format!("Could not read {}.", path.display()))?;
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+
+    let audio_filename = match extension.as_deref() {
+        Some("osu") => osu::audio_filename(&String::from_utf8_lossy(&data)),
+        Some("json") => document_audio(&data, DocumentFormat::Json),
+        Some("ron") => document_audio(&data, DocumentFormat::Ron),
+        _ => None,
+    };
+    let audio = read_audio(path, audio_filename.as_deref())?;
+
+    let record = match extension.as_deref() {
+        Some("swf") => return Ok(ChartFile::Swf(RecordPressBuilder::from_swf(data))),
+        Some("rrr") => RecordPressBuilder::from_bin(data).press(),
+        Some("osu") => RecordPressBuilder::from_osu(data, audio).press(),
+        Some("json") => RecordPressBuilder::from_json(data, audio).press(),
+        Some("ron") => RecordPressBuilder::from_ron(data, audio).press(),
+        _ => Err(anyhow::anyhow!(
+            "Unsupported chart file {}, expected .swf, .rrr, .osu, .json or .ron.",
+            path.display()
+        )),
+    }?;
+
+    Ok(ChartFile::Pressed {
+        record,
+        audio_filename,
+    })
+}
+
+fn document_audio(data: &[u8], format: DocumentFormat) -> Option<String> {
+    ChartDocument::read(&String::from_utf8_lossy(data), format)
+        .ok()
+        .and_then(|document| document.audio)
+}
+
+fn read_audio(chart_path: &Path, audio_filename: Option<&str>) -> Result<Vec<u8>> {
+    let Some(audio_filename) = audio_filename else {
+        return Ok(Vec::new());
+    };
+
+    // The name comes from inside the chart, keep it from reaching outside the chart's folder.
+    if Path::new(audio_filename).file_name() != Some(audio_filename.as_ref()) {
+        return Err(anyhow::anyhow!(
+            "Audio file {audio_filename} must be next to the chart."
+        ));
+    }
+
+    let audio_path = chart_path.with_file_name(audio_filename);
+    if !audio_path.exists() {
+        log::warn!("Audio file {} is missing.", audio_path.display());
+        return Ok(Vec::new());
+    }
+
+    fs::read(&audio_path).with_context(|| format!("Could not read {}.", audio_path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_audio() -> Result<()> {
+        let dir = tempfile::TempDir::new()?;
+        let chart_path = dir.path().join("chart.osu");
+        fs::write(dir.path().join("audio.mp3"), b"ID3")?;
+
+        assert_eq!(read_audio(&chart_path, Some("audio.mp3"))?, b"ID3");
+        assert!(read_audio(&chart_path, Some("missing.mp3"))?.is_empty());
+        assert!(read_audio(&chart_path, None)?.is_empty());
+
+        for name in ["../audio.mp3", "/etc/passwd", "music/audio.mp3", "..", ""] {
+            assert!(read_audio(&chart_path, Some(name)).is_err(), "{name}");
+        }
+        Ok(())
+    }
+}
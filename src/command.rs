@@ -1,5 +1,11 @@
 mod completions;
+mod convert;
+mod diff;
+mod download;
+mod inspect;
+mod lint;
 mod play;
+mod playlist;
 mod tui;
 
 use anyhow::Result;
@@ -13,6 +19,24 @@ pub enum Command {
     /// Start the user interface.
     Tui(tui::Args),
 
+    /// Check a chart file for mistakes.
+    Lint(lint::Args),
+
+    /// Print what a chart file contains.
+    Inspect(inspect::Args),
+
+    /// Convert chart files to another format.
+    Convert(convert::Args),
+
+    /// Show which notes changed between two versions of a chart.
+    Diff(diff::Args),
+
+    /// Download charts ahead of time so they can be played offline.
+    Download(download::Args),
+
+    /// Manage your own playlists, such as practice packs.
+    Playlist(playlist::Args),
+
     /// Generate completions for your shell.
     Completions(completions::Args),
 }
@@ -23,6 +47,12 @@ impl Command {
         match self {
             Play(args) => args.run(),
             Tui(args) => args.run(),
+            Lint(args) => args.run(),
+            Inspect(args) => args.run(),
+            Diff(args) => args.run(),
+            Download(args) => args.run(),
+            Playlist(args) => args.run(),
+            Convert(args) => args.run(),
             Completions(args) => args.run(),
         }
     }
@@ -0,0 +1,186 @@
+use crate::chart_file::{self, ChartFile};
+use anyhow::{Context, Result};
+use clap::{Parser, ValueEnum};
+use rrr_chart::{native, sm, AudioFormat, ChartDocument, DocumentFormat};
+use rrr_record::record::Record;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Chart files to convert (.swf, .rrr, .osu, .json or .ron)
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+
+    /// Format to write
+    #[arg(long, short, value_enum)]
+    to: Format,
+
+    /// Directory to write into, defaults to next to each chart
+    #[arg(long)]
+    out_dir: Option<PathBuf>,
+
+    /// Replace files that already exist
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Format {
+    /// Native binary chart with the audio inside (.rrr)
+    Native,
+    /// Chart document with the audio next to it (.json)
+    Json,
+    /// Chart document with the audio next to it (.ron)
+    Ron,
+    /// StepMania simfile with the audio next to it (.sm)
+    Sm,
+}
+
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Native => "rrr",
+            Format::Json => "json",
+            Format::Ron => "ron",
+            Format::Sm => "sm",
+        }
+    }
+}
+
+impl Args {
+    pub fn run(&self) -> Result<()> {
+        if let Some(out_dir) = &self.out_dir {
+            fs::create_dir_all(out_dir)
+                .with_context(|| format!("Could not create {}.", out_dir.display()))?;
+        }
+
+        let mut failures = 0_usize;
+        for file in &self.files {
+            match self.convert(file) {
+                Ok(output) => println!("{} -> {}", file.display(), output.display()),
+                Err(e) => {
+                    eprintln!("{}: {e:#}", file.display());
+                    failures += 1;
+                }
+            }
+        }
+
+        match failures {
+            0 => Ok(()),
+            count => Err(anyhow::anyhow!(
+                "Could not convert {count} of {} charts.",
+                self.files.len()
+            )),
+        }
+    }
+
+    fn convert(&self, file: &Path) -> Result<PathBuf> {
+        let (record, audio_filename) = match chart_file::open(file)? {
+            ChartFile::Swf(press) => (press.press()?, None),
+            ChartFile::Pressed {
+                record,
+                audio_filename,
+            } => (record, audio_filename),
+        };
+
+        // Stems can hold dots of their own, so the extension is appended rather than replaced.
+        let stem = file
+            .file_stem()
+            .ok_or_else(|| anyhow::anyhow!("File has no name."))?
+            .to_string_lossy();
+        let dir = match &self.out_dir {
+            Some(out_dir) => out_dir.as_path(),
+            None => file.parent().unwrap_or_else(|| Path::new("")),
+        };
+        let output = dir.join(format!("{stem}.{}", self.to.extension()));
+        if output == file {
+            return Err(anyhow::anyhow!("Chart is already in this format."));
+        }
+        // Checked before the audio is written so a refused chart leaves nothing behind.
+        self.check_overwrite(&output)?;
+
+        // Swf and native charts carry mp3 audio, other formats keep the extension they came with.
+        let audio_extension = audio_filename
+            .as_deref()
+            .and_then(|audio_filename| Path::new(audio_filename).extension())
+            .map_or_else(|| "mp3".into(), |extension| extension.to_string_lossy());
+        let audio_name = PathBuf::from(format!("{stem}.{audio_extension}"));
+        let data = match self.to {
+            Format::Native => native::write(
+                &record.chart,
+                &record.metadata,
+                &record.mp3,
+                record.audio_info.as_ref(),
+            ),
+            Format::Json => self.document(&record, dir, &audio_name, DocumentFormat::Json)?,
+            Format::Ron => self.document(&record, dir, &audio_name, DocumentFormat::Ron)?,
+            Format::Sm => {
+                let music = if self.write_audio(&record, &dir.join(&audio_name))? {
+                    audio_name.to_string_lossy().into_owned()
+                } else {
+                    String::new()
+                };
+                sm::write_sm(&record.chart, &record.metadata, &music).into_bytes()
+            }
+        };
+
+        self.write_file(&output, &data)?;
+        Ok(output)
+    }
+
+    fn document(
+        &self,
+        record: &Record,
+        dir: &Path,
+        audio_name: &Path,
+        format: DocumentFormat,
+    ) -> Result<Vec<u8>> {
+        let mut document = ChartDocument::new(&record.chart, &record.metadata);
+        if self.write_audio(record, &dir.join(audio_name))? {
+            document = document.with_audio(&audio_name.to_string_lossy());
+        }
+        Ok(document.write(format)?.into_bytes())
+    }
+
+    /// Writes the record's audio to `path`, returns whether there was any audio to write.
+    fn write_audio(&self, record: &Record, path: &Path) -> Result<bool> {
+        if record.mp3.is_empty() {
+            return Ok(false);
+        }
+
+        if let Some(audio_info) = record.audio_info {
+            if audio_info.format != AudioFormat::Mp3 {
+                return Err(anyhow::anyhow!(
+                    "{:?} audio can only be kept in a native chart.",
+                    audio_info.format
+                ));
+            }
+        }
+
+        // Converting a document next to its own audio finds the same file already there.
+        if matches!(fs::read(path), Ok(existing) if existing == record.mp3) {
+            return Ok(true);
+        }
+
+        self.write_file(path, &record.mp3)?;
+        Ok(true)
+    }
+
+    fn write_file(&self, path: &Path, data: &[u8]) -> Result<()> {
+        self.check_overwrite(path)?;
+        fs::write(path, data).with_context(|| format!("Could not write {}.", path.display()))
+    }
+
+    fn check_overwrite(&self, path: &Path) -> Result<()> {
+        if !self.force && path.exists() {
+            return Err(anyhow::anyhow!(
+                "{} already exists, use --force to replace it.",
+                path.display()
+            ));
+        }
+        Ok(())
+    }
+}
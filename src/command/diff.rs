@@ -0,0 +1,53 @@
+use crate::chart_file;
+use anyhow::Result;
+use clap::Parser;
+use rrr_chart::diff::{self, DiffOptions};
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// The chart as it was (.swf, .rrr, .osu, .json or .ron)
+    before: PathBuf,
+
+    /// The chart as it is now
+    after: PathBuf,
+
+    /// Treat notes on the same lane this many milliseconds apart as unchanged
+    #[arg(long, default_value_t = DiffOptions::default().tolerance)]
+    tolerance: u32,
+
+    /// Report notes shifted by up to this many milliseconds as moved instead of removed and added
+    #[arg(long, default_value_t = DiffOptions::default().max_shift)]
+    max_shift: u32,
+}
+
+impl Args {
+    pub fn run(&self) -> Result<()> {
+        let before = chart_file::read_record(&self.before)?;
+        let after = chart_file::read_record(&self.after)?;
+
+        if before.chart.lane_count != after.chart.lane_count {
+            println!(
+                "Lane count changed from {} to {}.",
+                before.chart.lane_count, after.chart.lane_count
+            );
+        }
+
+        let options = DiffOptions {
+            tolerance: self.tolerance,
+            max_shift: self.max_shift,
+        };
+
+        let changes = diff::diff(&before.chart, &after.chart, &options);
+        for change in &changes {
+            println!("{change}");
+        }
+
+        match changes.len() {
+            0 => println!("No note changes."),
+            count => println!("{count} changes."),
+        }
+
+        Ok(())
+    }
+}
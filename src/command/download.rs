@@ -0,0 +1,148 @@
+use crate::song;
+use anyhow::{Context, Result};
+use clap::{builder::TypedValueParser, ArgGroup, Parser};
+use rrr_data::cache::Cache;
+use rrr_fetch::{client, download::DownloadManager, ffr};
+use rrr_playlist::query::Query;
+use std::{
+    collections::{HashMap, HashSet},
+    io::{self, Write},
+    ops::RangeInclusive,
+    path::PathBuf,
+};
+
+/// Charts already downloaded are skipped, so running the same download again resumes it.
+/// Downloaded charts stay in the cache, playing other songs doesn't evict them.
+#[derive(Debug, Parser)]
+#[command(group(ArgGroup::new("songs").required(true).args(["all", "genre", "level"])))]
+pub struct Args {
+    /// Download every song in the playlist
+    #[arg(long)]
+    all: bool,
+
+    /// Download the songs in this genre
+    #[arg(long)]
+    genre: Option<i64>,
+
+    /// Download the songs with levels in this inclusive range, such as 100..200
+    #[arg(long, value_parser = parse_range)]
+    level: Option<RangeInclusive<i64>>,
+
+    /// Charts to download at the same time
+    #[arg(long, short, default_value_t = DownloadManager::DEFAULT_CONCURRENCY,
+          value_parser = clap::value_parser!(u8).range(1..=16).map(usize::from))]
+    jobs: usize,
+
+    /// Server to download the playlist and charts from, such as a local mirror
+    #[arg(long, default_value = ffr::DEFAULT_BASE_URL)]
+    base_url: String,
+
+    /// JSON file of the server's paths, for servers laid out differently to FFR
+    #[arg(long, conflicts_with = "base_url")]
+    endpoints: Option<PathBuf>,
+}
+
+impl Args {
+    pub fn run(&self) -> Result<()> {
+        let cache =
+            Cache::open().context("Charts are downloaded into the cache, but there is none.")?;
+
+        let endpoints = song::endpoints(&self.base_url, self.endpoints.as_deref())?;
+        let playlist = match client::fetch_and_cache_playlist(&cache, &endpoints) {
+            Ok(playlist) => playlist,
+            Err(e) => {
+                let playlist = client::cached_playlist(&cache, &endpoints)
+                    .ok_or(e)
+                    .context("Could not download the playlist.")?;
+                eprintln!("Using the playlist from last time, the newest could not be downloaded.");
+                playlist
+            }
+        };
+
+        let songs = self.query().run(&playlist);
+        let mut seen = HashSet::new();
+        let hashes: Vec<String> = songs
+            .iter()
+            .map(|song| song.hash_load.clone())
+            .filter(|hash| seen.insert(hash.clone()))
+            .collect();
+        if hashes.is_empty() {
+            return Err(anyhow::anyhow!("No songs in the playlist match."));
+        }
+
+        println!(
+            "Downloading {} charts into {}.",
+            hashes.len(),
+            cache.dir().display()
+        );
+        let manager = DownloadManager::new(cache, endpoints).with_concurrency(self.jobs);
+        // Let the charts in flight stop cleanly so none are left half written.
+        let cancel = manager.cancel_handle();
+        ctrlc::set_handler(move || cancel.cancel()).context("Could not listen for Ctrl-C.")?;
+        let report = manager.download(&hashes, |progress| {
+            eprint!(
+                "\r{}/{} charts, {} failed",
+                progress.finished(),
+                progress.total,
+                progress.failed
+            );
+            let _ = io::stderr().flush();
+        });
+        eprintln!();
+
+        let names: HashMap<&str, &str> = songs
+            .iter()
+            .map(|song| (song.hash_load.as_str(), song.name.as_str()))
+            .collect();
+        for (hash, error) in &report.failures {
+            let name = names.get(hash.as_str()).copied().unwrap_or(hash.as_str());
+            eprintln!("{name}: {error}");
+        }
+
+        let progress = report.progress;
+        println!(
+            "Downloaded {}, {} already cached, {} failed.",
+            progress.downloaded, progress.cached, progress.failed
+        );
+
+        if report.cancelled {
+            return Err(anyhow::anyhow!(
+                "Cancelled, run the same command again to finish downloading."
+            ));
+        }
+        match progress.failed {
+            0 => Ok(()),
+            failed => Err(anyhow::anyhow!(
+                "Could not download {failed} charts, run the same command again to retry them."
+            )),
+        }
+    }
+
+    /// Only one of `--all`, `--genre` and `--level` is given.
+    fn query(&self) -> Query {
+        match (self.genre, &self.level) {
+            (Some(genre), _) => Query::new().with_genre(genre),
+            (_, Some(level)) => Query::new().with_level(level.clone()),
+            _ => Query::new(),
+        }
+    }
+}
+
+fn parse_range(range: &str) -> Result<RangeInclusive<i64>, String> {
+    let parse = |bound: &str| {
+        bound
+            .trim()
+            .parse::<i64>()
+            .map_err(|_| format!("`{bound}` is not a level."))
+    };
+
+    let (start, end) = match range.split_once("..") {
+        Some((start, end)) => (parse(start)?, parse(end.trim_start_matches('='))?),
+        None => (parse(range)?, parse(range)?),
+    };
+    if start > end {
+        return Err(format!("The range {start}..{end} is empty."));
+    }
+
+    Ok(start..=end)
+}
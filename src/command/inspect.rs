@@ -0,0 +1,144 @@
+use crate::chart_file;
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+use rrr_chart::{ChartDocument, DocumentFormat, NoteColor, NoteKind};
+use rrr_record::record::Record;
+use rrr_types::{Lane, LaneCount};
+use std::{collections::BTreeMap, path::PathBuf};
+
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Chart file to inspect (.swf, .rrr, .osu, .json or .ron)
+    file: PathBuf,
+
+    /// Also print every note
+    #[arg(long, value_enum)]
+    notes: Option<NotesFormat>,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum NotesFormat {
+    Table,
+    Json,
+}
+
+impl Args {
+    pub fn run(&self) -> Result<()> {
+        let record = chart_file::read_record(&self.file)?;
+
+        print_summary(&record);
+
+        match self.notes {
+            Some(NotesFormat::Table) => print_table(&record),
+            Some(NotesFormat::Json) => println!(
+                "{}",
+                ChartDocument::new(&record.chart, &record.metadata).write(DocumentFormat::Json)?
+            ),
+            None => {}
+        }
+
+        Ok(())
+    }
+}
+
+fn print_summary(record: &Record) {
+    let chart = &record.chart;
+    let lanes = chart.lane_count;
+    let length = chart.notes.iter().map(|note| note.timestamp).max();
+
+    println!("Title:   {}", record.metadata.title);
+    println!("Artist:  {}", record.metadata.artist);
+    println!("Author:  {}", record.metadata.author);
+    println!("Hash:    {}", record.hash);
+    println!("Lanes:   {lanes}");
+    println!("Notes:   {}", chart.notes.len());
+    println!(
+        "Length:  {}",
+        length.map_or_else(|| "-".to_string(), format_ms)
+    );
+    println!("Timing:  {} timing points", chart.timing.len());
+    println!("Audio:   {}", audio_summary(record));
+
+    let mut colors: BTreeMap<NoteColor, usize> = BTreeMap::new();
+    let mut by_lane: BTreeMap<_, usize> = BTreeMap::new();
+    let mut kinds: BTreeMap<NoteKind, usize> = BTreeMap::new();
+    for note in &chart.notes {
+        *colors.entry(note.color).or_default() += 1;
+        *by_lane.entry(note.lane).or_default() += 1;
+        *kinds.entry(note.kind).or_default() += 1;
+    }
+
+    println!("\nBy color:");
+    for (color, count) in colors {
+        println!("  {:<10}{count}", format!("{color:?}"));
+    }
+
+    println!("\nBy lane:");
+    for lane in lanes.lanes() {
+        let count = by_lane.get(&lane).copied().unwrap_or_default();
+        println!("  {:<10}{count}", direction_label(lane, lanes));
+    }
+
+    if kinds.keys().any(|kind| !kind.is_judged()) {
+        println!("\nBy kind:");
+        for (kind, count) in kinds {
+            println!("  {:<10}{count}", format!("{kind:?}"));
+        }
+    }
+}
+
+fn audio_summary(record: &Record) -> String {
+    if record.mp3.is_empty() {
+        return "none".to_string();
+    }
+
+    let mut parts = Vec::new();
+    if let Some(info) = record.audio_info {
+        parts.push(format!("{:?}", info.format));
+        parts.push(format!("{} Hz", info.sample_rate));
+        parts.push(if info.is_stereo { "stereo" } else { "mono" }.to_string());
+        parts.push(format!("{} ms latency", info.latency_ms()));
+    }
+    parts.push(format!("{:.1} KiB", record.mp3.len() as f64 / 1024.));
+    match rrr_audio::duration(&record.mp3) {
+        Ok(duration) => parts.push(format_ms(
+            u32::try_from(duration.as_millis()).unwrap_or(u32::MAX),
+        )),
+        Err(e) => parts.push(format!("unreadable ({e})")),
+    }
+
+    parts.join(", ")
+}
+
+fn print_table(record: &Record) {
+    let lanes = record.chart.lane_count;
+
+    println!(
+        "\n{:>6} {:>10} {:>6}  {:<10}{:<8}kind",
+        "#", "time", "beat", "lane", "color"
+    );
+    for (index, note) in record.chart.notes.iter().enumerate() {
+        println!(
+            "{index:>6} {:>10} {:>6}  {:<10}{:<8}{:?}",
+            format_ms(note.timestamp),
+            note.beat_position,
+            direction_label(note.lane, lanes),
+            format!("{:?}", note.color),
+            note.kind
+        );
+    }
+}
+
+/// 4K lanes also show which arrow they are.
+fn direction_label(lane: Lane, lanes: LaneCount) -> String {
+    if lanes == LaneCount::FOUR {
+        format!("{} ({:?})", lane.0, lane.direction(lanes))
+    } else {
+        lane.0.to_string()
+    }
+}
+
+/// Formats milliseconds as `m:ss.mmm`.
+fn format_ms(ms: u32) -> String {
+    format!("{}:{:02}.{:03}", ms / 60_000, ms / 1000 % 60, ms % 1000)
+}
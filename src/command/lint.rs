@@ -0,0 +1,53 @@
+use crate::chart_file;
+use anyhow::Result;
+use clap::Parser;
+use rrr_chart::lint::{self, LintOptions};
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Chart file to check (.swf, .rrr, .osu, .json or .ron)
+    file: PathBuf,
+
+    /// Report notes on the same lane closer together than this many milliseconds
+    #[arg(long, default_value_t = LintOptions::default().minijack_threshold)]
+    minijack: u32,
+
+    /// Frames a note's beat position may differ from its timestamp
+    #[arg(long, default_value_t = LintOptions::default().beat_tolerance)]
+    beat_tolerance: u32,
+}
+
+impl Args {
+    pub fn run(&self) -> Result<()> {
+        let record = chart_file::read_record(&self.file)?;
+
+        let audio_length = if record.mp3.is_empty() {
+            None
+        } else {
+            match rrr_audio::duration(&record.mp3) {
+                Ok(duration) => Some(u32::try_from(duration.as_millis()).unwrap_or(u32::MAX)),
+                Err(e) => {
+                    eprintln!("Could not read audio length, skipping that check: {e}");
+                    None
+                }
+            }
+        };
+
+        let options = LintOptions {
+            audio_length,
+            minijack_threshold: self.minijack,
+            beat_tolerance: self.beat_tolerance,
+        };
+
+        let lints = lint::lint(&record.chart, &options);
+        for problem in &lints {
+            println!("{}: {problem}", self.file.display());
+        }
+
+        match lints.len() {
+            0 => Ok(()),
+            count => Err(anyhow::anyhow!("Found {count} problems.")),
+        }
+    }
+}
@@ -1,18 +1,59 @@
-use anyhow::Result;
+use crate::{
+    chart_file::{self, ChartFile},
+    song,
+};
+use anyhow::{Context, Result};
 use clap::Parser;
 use rrr_config::Config;
-use rrr_fetch::{platform::Fetcher, FetchProgress};
+use rrr_fetch::ffr;
 use rrr_game::{
     builder::RustRustRevolutionBuilder,
     prelude::{rrr_render, Play, Turntable},
 };
+use rrr_noteskin::Noteskin;
 use rrr_record::RecordPressBuilder;
+use rrr_settings::Settings;
+use rrr_types::SongID;
 use rrr_window::{prelude::EventLoopBuilder, Window};
+use std::{fs, path::PathBuf};
 
 #[derive(Debug, Parser)]
 pub struct Args {
     /// ID of song to play
-    song_id: u16,
+    #[arg(required_unless_present = "file", conflicts_with = "file")]
+    song_id: Option<u16>,
+
+    /// Play a chart file from disk instead (.swf, .rrr, .osu, .json or .ron)
+    #[arg(long)]
+    file: Option<PathBuf>,
+
+    /// Server to download the playlist and charts from, such as a local mirror
+    #[arg(long, default_value = ffr::DEFAULT_BASE_URL, conflicts_with = "file")]
+    base_url: String,
+
+    /// JSON file of the server's paths, for servers laid out differently to FFR
+    #[arg(long, conflicts_with = "base_url", conflicts_with = "file")]
+    endpoints: Option<PathBuf>,
+
+    /// Settings to play with, as JSON
+    #[arg(long)]
+    settings: Option<PathBuf>,
+
+    /// Milliseconds a note takes to scroll to the receptors, overrides the settings file
+    #[arg(long)]
+    scroll_speed: Option<u32>,
+
+    /// Milliseconds to shift judgement by, overrides the settings file
+    #[arg(long, allow_hyphen_values = true)]
+    judge_offset: Option<i8>,
+
+    /// Noteskin image laid out like the default noteskin
+    #[arg(long)]
+    noteskin: Option<PathBuf>,
+
+    /// Width and height of one note in the noteskin image, in pixels
+    #[arg(long, default_value_t = 64, requires = "noteskin")]
+    note_size: usize,
 }
 
 impl Args {
@@ -23,36 +64,23 @@ impl Args {
             ));
         }
 
-        let url = format!(
-            "https://www.flashflashrevolution.com/game/r3/r3-songLoad.php?id={}&type=ChartFFR_music",
-            "8e058c682f875b36fac6fa065c33fd88"
-        );
-
-        let mut fetcher = Fetcher::new(url);
-
-        assert!(fetcher.is_ok(), "{:?}", fetcher.err());
-
-        if let Ok(fetcher) = fetcher.as_mut() {
-            loop {
-                let progress = fetcher.fetch();
-                if let Ok(progress) = progress {
-                    match progress {
-                        FetchProgress::Fetching(percent) => println!("%{:?} complete", percent),
-                        FetchProgress::Finished => break,
-                        FetchProgress::Error(_) => todo!(),
-                    }
-                }
+        let (chart, ffr_song) = match (&self.file, self.song_id) {
+            (Some(file), _) => (chart_file::open(file)?, None),
+            (None, Some(id)) => {
+                let (ffr_song, data) = song::download_chart(
+                    &SongID(id),
+                    &song::endpoints(&self.base_url, self.endpoints.as_deref())?,
+                    song::open_cache().as_ref(),
+                )?;
+                (
+                    ChartFile::Swf(RecordPressBuilder::from_swf(data)),
+                    Some(ffr_song),
+                )
             }
-        }
-
-        let data = if let Ok(fetcher) = fetcher {
-            fetcher.consume()
-        } else {
-            return Err(anyhow::anyhow!("Failed to fetch."));
+            (None, None) => return Err(anyhow::anyhow!("Choose a song ID or a chart file.")),
         };
-
-        let record_press = RecordPressBuilder::from_swf(data);
-        let record = record_press.press();
+        let settings = self.settings()?;
+        let noteskin = self.noteskin()?;
 
         let config = Config::default();
         let mut event_loop = EventLoopBuilder::new().build();
@@ -63,13 +91,59 @@ impl Args {
                 .await
         })?;
 
-        let turntable = Turntable::load(record.unwrap());
-        let play = Play::new(turntable);
+        let record = match chart {
+            ChartFile::Swf(press) => match window.press(press)? {
+                Some(record) => record,
+                None => return Ok(()),
+            },
+            ChartFile::Pressed { record, .. } => record,
+        };
+        let record = match &ffr_song {
+            Some(ffr_song) => record.with_song(ffr_song),
+            None => record,
+        };
+
+        let turntable = Turntable::load(record);
+        let play = Play::new(turntable).with_settings(settings.core.clone());
 
         let mut rrr = RustRustRevolutionBuilder::with_play(play)
             .with_renderer(renderer)
+            .with_settings(settings)
+            .with_noteskin(noteskin)
             .build();
         window.run_once(&mut rrr);
         Ok(())
     }
+
+    fn settings(&self) -> Result<Settings> {
+        let mut settings = match &self.settings {
+            Some(path) => {
+                let json = fs::read_to_string(path)
+                    .with_context(|| format!("Could not read {}.", path.display()))?;
+                serde_json::from_str(&json)
+                    .with_context(|| format!("Invalid settings in {}.", path.display()))?
+            }
+            None => Settings::default(),
+        };
+
+        if let Some(scroll_speed) = self.scroll_speed {
+            settings.scroll_speed = scroll_speed;
+        }
+        if let Some(judge_offset) = self.judge_offset {
+            settings.core.judge_offset = judge_offset;
+        }
+
+        Ok(settings)
+    }
+
+    fn noteskin(&self) -> Result<Noteskin> {
+        let Some(path) = &self.noteskin else {
+            return Ok(Noteskin::default());
+        };
+
+        let image =
+            fs::read(path).with_context(|| format!("Could not read {}.", path.display()))?;
+        Noteskin::from_image(&image, self.note_size)
+            .with_context(|| format!("Invalid noteskin {}.", path.display()))
+    }
 }
@@ -0,0 +1,107 @@
+use crate::song;
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use rrr_fetch::{client, ffr};
+use rrr_playlist::user::UserPlaylist;
+use rrr_types::SongID;
+use std::path::PathBuf;
+
+/// Playlists are lists of FFR song IDs, shared as JSON files.
+#[derive(Debug, Parser)]
+pub struct Args {
+    #[command(subcommand)]
+    action: Action,
+
+    /// Server the songs' names come from, such as a local mirror
+    #[arg(long, global = true, default_value = ffr::DEFAULT_BASE_URL)]
+    base_url: String,
+
+    /// JSON file of the server's paths, for servers laid out differently to FFR
+    #[arg(long, global = true, conflicts_with = "base_url")]
+    endpoints: Option<PathBuf>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Action {
+    /// List your playlists, or the songs in one
+    List { name: Option<String> },
+
+    /// Make a playlist from song IDs, replacing one with the same name
+    Create {
+        name: String,
+        #[arg(required = true)]
+        songs: Vec<u16>,
+    },
+
+    /// Add a playlist file, replacing one with the same name
+    Import { file: PathBuf },
+
+    /// Write a playlist to a file to share it
+    Export { name: String, file: PathBuf },
+
+    /// Delete a playlist
+    Remove { name: String },
+}
+
+impl Args {
+    pub fn run(&self) -> Result<()> {
+        let mut playlists = UserPlaylist::load_all().context("Could not read your playlists.")?;
+
+        match &self.action {
+            Action::List { name: None } => {
+                for playlist in &playlists {
+                    println!("{} ({} songs)", playlist.name, playlist.songs.len());
+                }
+                return Ok(());
+            }
+            Action::List { name: Some(name) } => return self.list(find(&playlists, name)?),
+            Action::Create { name, songs } => replace(
+                &mut playlists,
+                UserPlaylist {
+                    songs: songs.iter().copied().map(SongID).collect(),
+                    ..UserPlaylist::new(name)
+                },
+            ),
+            Action::Import { file } => {
+                let playlist = UserPlaylist::open(file)?;
+                println!("Imported {}.", playlist.name);
+                replace(&mut playlists, playlist);
+            }
+            Action::Export { name, file } => return Ok(find(&playlists, name)?.save(file)?),
+            Action::Remove { name } => {
+                find(&playlists, name)?;
+                playlists.retain(|playlist| &playlist.name != name);
+            }
+        }
+
+        UserPlaylist::save_all(&playlists).context("Could not save your playlists.")
+    }
+
+    /// Name each song from the cached FFR playlist, or only its ID when it isn't cached.
+    fn list(&self, playlist: &UserPlaylist) -> Result<()> {
+        let endpoints = song::endpoints(&self.base_url, self.endpoints.as_deref())?;
+        let ffr = song::open_cache().and_then(|cache| client::cached_playlist(&cache, &endpoints));
+
+        for id in &playlist.songs {
+            match ffr.as_ref().and_then(|ffr| ffr.song(id)) {
+                Some(song) => println!("{:>5}  {} - {}", id.0, song.name, song.author),
+                None => println!("{:>5}", id.0),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn find<'a>(playlists: &'a [UserPlaylist], name: &str) -> Result<&'a UserPlaylist> {
+    playlists
+        .iter()
+        .find(|playlist| playlist.name == name)
+        .ok_or_else(|| anyhow::anyhow!("You have no playlist named {name}."))
+}
+
+fn replace(playlists: &mut Vec<UserPlaylist>, playlist: UserPlaylist) {
+    match playlists.iter_mut().find(|old| old.name == playlist.name) {
+        Some(old) => *old = playlist,
+        None => playlists.push(playlist),
+    }
+}
@@ -1,11 +1,41 @@
-use anyhow::Result;
+use crate::song;
+use anyhow::{Context, Result};
 use clap::Parser;
+use rrr_fetch::ffr;
+use rrr_playlist::user::UserPlaylist;
+use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
-pub struct Args {}
+pub struct Args {
+    /// Server to download charts from, such as a local mirror
+    #[arg(long, default_value = ffr::DEFAULT_BASE_URL)]
+    base_url: String,
+
+    /// JSON file of the server's paths, for servers laid out differently to FFR
+    #[arg(long, conflicts_with = "base_url")]
+    endpoints: Option<PathBuf>,
+
+    /// Only list the songs in one of your playlists
+    #[arg(long)]
+    playlist: Option<String>,
+}
 
 impl Args {
     pub fn run(&self) -> Result<()> {
-        rrr_tui::init()
+        let playlist = match &self.playlist {
+            Some(name) => Some(
+                UserPlaylist::load_all()
+                    .context("Could not read your playlists.")?
+                    .into_iter()
+                    .find(|playlist| &playlist.name == name)
+                    .ok_or_else(|| anyhow::anyhow!("You have no playlist named {name}."))?,
+            ),
+            None => None,
+        };
+
+        rrr_tui::init(
+            song::endpoints(&self.base_url, self.endpoints.as_deref())?,
+            playlist,
+        )
     }
 }
@@ -1,4 +1,6 @@
+mod chart_file;
 mod command;
+mod song;
 
 use anyhow::Result;
 use clap::Parser;
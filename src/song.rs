@@ -0,0 +1,123 @@
+use anyhow::{Context, Result};
+use rrr_data::cache::Cache;
+use rrr_fetch::{
+    client,
+    ffr::Endpoints,
+    platform::{fetch_all, fetch_cached},
+};
+use rrr_playlist::{FFRPlaylist, Song};
+use rrr_types::SongID;
+use std::{fs, path::Path};
+
+/// The download cache, downloads still work without one.
+pub fn open_cache() -> Option<Cache> {
+    match Cache::open() {
+        Ok(cache) => Some(cache),
+        Err(e) => {
+            log::warn!("Downloading without a cache: {e}");
+            None
+        }
+    }
+}
+
+/// The server's paths from an endpoints file, or FFR's paths on `base_url` without one.
+///
+/// The file is [`Endpoints`] as JSON, fields left out keep FFR's.
+pub fn endpoints(base_url: &str, file: Option<&Path>) -> Result<Endpoints> {
+    let Some(path) = file else {
+        return Ok(Endpoints::with_base_url(base_url));
+    };
+
+    let json =
+        fs::read_to_string(path).with_context(|| format!("Could not read {}.", path.display()))?;
+    serde_json::from_str(&json).with_context(|| format!("Invalid endpoints in {}.", path.display()))
+}
+
+/// Download the swf chart for song `id`, along with how the playlist lists it.
+///
+/// Charts and the playlist are cached, the playlist is only fetched again when it doesn't
+/// know `id`.
+pub fn download_chart(
+    id: &SongID,
+    endpoints: &Endpoints,
+    cache: Option<&Cache>,
+) -> Result<(Song, Vec<u8>)> {
+    let mut playlist = cache.and_then(|cache| client::cached_playlist(cache, endpoints));
+    if playlist
+        .as_ref()
+        .and_then(|playlist| playlist.song(id))
+        .is_none()
+    {
+        let fetched = match cache {
+            Some(cache) => client::fetch_and_cache_playlist(cache, endpoints),
+            None => client::fetch_playlist(endpoints).map(FFRPlaylist::from),
+        };
+        playlist = Some(fetched.context("Could not download the playlist.")?);
+    }
+
+    let song = playlist
+        .as_ref()
+        .and_then(|playlist| playlist.song(id))
+        .ok_or_else(|| anyhow::anyhow!("Unknown song ID {}, it is not in the playlist.", id.0))?;
+
+    let url = endpoints.chart_url(&song.hash_load);
+    let data = match cache {
+        Some(cache) => fetch_cached(cache, &song.hash_load, url),
+        None => fetch_all(url),
+    }
+    .with_context(|| format!("Could not download {}.", song.name))?;
+    Ok((song.clone(), data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rrr_fetch::stand_in::StandIn;
+
+    const PLAYLIST: &[u8] = include_bytes!("../crates/rrr_fetch/src/test_assets/playlist.json");
+
+    fn stand_in() -> StandIn {
+        StandIn::serve(&[
+            ("/r3-playlist.v2.php", PLAYLIST),
+            (
+                "/r3-songLoad.php?id=8e058c682f875b36fac6fa065c33fd88&type=ChartFFR_music",
+                b"FWS chart",
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_download_chart() -> Result<()> {
+        let stand_in = stand_in();
+        let endpoints = stand_in.endpoints();
+
+        let (song, data) = download_chart(&SongID(1), &endpoints, None)?;
+        assert_eq!(song.name, "Exploration of Space");
+        assert_eq!(data, b"FWS chart");
+
+        let error = download_chart(&SongID(2), &endpoints, None).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unknown song ID 2, it is not in the playlist."
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_download_chart_offline() -> Result<()> {
+        let dir = tempfile::TempDir::new()?;
+        let cache = Cache::at(dir.path().to_path_buf());
+
+        let stand_in = stand_in();
+        let endpoints = stand_in.endpoints();
+        download_chart(&SongID(1), &endpoints, Some(&cache))?;
+        drop(stand_in);
+
+        // Both the playlist and the chart come from the cache now.
+        assert_eq!(
+            download_chart(&SongID(1), &endpoints, Some(&cache))?.1,
+            b"FWS chart"
+        );
+        Ok(())
+    }
+}